@@ -0,0 +1,38 @@
+mod reader;
+mod writer;
+
+pub use reader::*;
+pub use writer::*;
+
+use giputils::hash::GHashMap;
+
+/// An and-inverter graph over AIGER literals `2 * var + complement`, where
+/// literal 0 is false and 1 is true.
+#[derive(Clone, Default, Debug)]
+struct Aiger {
+    max_var: u32,
+    inputs: Vec<u32>,
+    latches: Vec<AigerLatch>,
+    outputs: Vec<u32>,
+    bads: Vec<u32>,
+    constraints: Vec<u32>,
+    ands: Vec<AigerAnd>,
+    /// Symbols of inputs and latches by their position.
+    input_symbols: GHashMap<usize, String>,
+    latch_symbols: GHashMap<usize, String>,
+}
+
+/// A latch whose `init` is `None` when uninitialized.
+#[derive(Clone, Copy, Debug)]
+struct AigerLatch {
+    lit: u32,
+    next: u32,
+    init: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AigerAnd {
+    lhs: u32,
+    rhs0: u32,
+    rhs1: u32,
+}
@@ -0,0 +1,303 @@
+use super::{Aiger, AigerAnd, AigerLatch};
+use crate::{ParseError, Sort, Term, TermManager, TransitionSys};
+use giputils::hash::{GHashMap, GHashSet};
+use std::{fs, path::Path};
+
+struct AigerReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> AigerReader<'a> {
+    fn line(&mut self) -> Result<&'a str, ParseError> {
+        if self.pos >= self.data.len() {
+            return Err(ParseError::syntax("unexpected end of aiger"));
+        }
+        let start = self.pos;
+        while self.pos < self.data.len() && self.data[self.pos] != b'\n' {
+            self.pos += 1;
+        }
+        let line = &self.data[start..self.pos];
+        self.pos += 1;
+        str::from_utf8(line)
+            .map(|l| l.trim())
+            .map_err(|_| ParseError::syntax("invalid utf-8 in aiger"))
+    }
+
+    /// Reads a line of at least `min` literals.
+    fn lits(&mut self, min: usize) -> Result<Vec<u32>, ParseError> {
+        let line = self.line()?;
+        let lits = line
+            .split_whitespace()
+            .map(|l| {
+                l.parse()
+                    .map_err(|_| ParseError::syntax(format!("invalid aiger literal {l}")))
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+        if lits.len() < min {
+            return Err(ParseError::syntax(format!("invalid aiger line {line}")));
+        }
+        Ok(lits)
+    }
+
+    fn delta(&mut self) -> Result<u32, ParseError> {
+        let mut x = 0;
+        let mut shift = 0;
+        loop {
+            let Some(&b) = self.data.get(self.pos) else {
+                return Err(ParseError::syntax("unexpected end of aiger"));
+            };
+            if shift > 28 {
+                return Err(ParseError::syntax("aiger delta overflows"));
+            }
+            self.pos += 1;
+            x |= ((b & 0x7f) as u32) << shift;
+            if b & 0x80 == 0 {
+                return Ok(x);
+            }
+            shift += 7;
+        }
+    }
+}
+
+impl Aiger {
+    fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = AigerReader { data, pos: 0 };
+        let header: Vec<&str> = reader.line()?.split_whitespace().collect();
+        let binary = match header.first() {
+            Some(&"aag") => false,
+            Some(&"aig") => true,
+            f => {
+                return Err(ParseError::syntax(format!(
+                    "invalid aiger format {}",
+                    f.unwrap_or(&"")
+                )));
+            }
+        };
+        let num = header[1..]
+            .iter()
+            .map(|n| n.parse())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| ParseError::syntax("invalid aiger header"))?;
+        if num.len() < 5 {
+            return Err(ParseError::syntax("invalid aiger header"));
+        }
+        let field = |i: usize| num.get(i).copied().unwrap_or(0) as usize;
+        let (ni, nl, no, na, nb, nc) = (field(1), field(2), field(3), field(4), field(5), field(6));
+        if field(7) != 0 || field(8) != 0 {
+            return Err(ParseError::unsupported("aiger justice and fairness"));
+        }
+        if binary && ni + nl + na != field(0) {
+            return Err(ParseError::syntax("invalid binary aiger header"));
+        }
+        let mut aig = Aiger {
+            max_var: num[0],
+            ..Default::default()
+        };
+        for i in 0..ni {
+            let lit = if binary {
+                2 * (i as u32 + 1)
+            } else {
+                reader.lits(1)?[0]
+            };
+            aig.inputs.push(lit);
+        }
+        for i in 0..nl {
+            let mut l = reader.lits(if binary { 1 } else { 2 })?;
+            if binary {
+                l.insert(0, 2 * (ni + i + 1) as u32);
+            }
+            let init = match l.get(2) {
+                None | Some(0) => Some(false),
+                Some(1) => Some(true),
+                Some(i) if *i == l[0] => None,
+                Some(i) => {
+                    return Err(ParseError::syntax(format!("invalid aiger latch init {i}")));
+                }
+            };
+            aig.latches.push(AigerLatch {
+                lit: l[0],
+                next: l[1],
+                init,
+            });
+        }
+        for (n, sec) in [
+            (no, &mut aig.outputs),
+            (nb, &mut aig.bads),
+            (nc, &mut aig.constraints),
+        ] {
+            for _ in 0..n {
+                sec.push(reader.lits(1)?[0]);
+            }
+        }
+        for i in 0..na {
+            let and = if binary {
+                let lhs = 2 * (ni + nl + i + 1) as u32;
+                let (d0, d1) = (reader.delta()?, reader.delta()?);
+                let rhs0 = lhs.checked_sub(d0);
+                let rhs1 = rhs0.and_then(|r| r.checked_sub(d1));
+                match (rhs0, rhs1) {
+                    (Some(rhs0), Some(rhs1)) => AigerAnd { lhs, rhs0, rhs1 },
+                    _ => return Err(ParseError::syntax(format!("invalid aiger and {lhs}"))),
+                }
+            } else {
+                let l = reader.lits(3)?;
+                AigerAnd {
+                    lhs: l[0],
+                    rhs0: l[1],
+                    rhs1: l[2],
+                }
+            };
+            aig.ands.push(and);
+        }
+        while reader.pos < data.len() {
+            let line = reader.line()?;
+            if line == "c" {
+                break;
+            }
+            let Some((pos, name)) = line.get(1..).and_then(|l| l.split_once(' ')) else {
+                continue;
+            };
+            let pos = pos
+                .parse()
+                .map_err(|_| ParseError::syntax(format!("invalid aiger symbol {line}")))?;
+            match line.as_bytes()[0] {
+                b'i' => aig.input_symbols.insert(pos, name.to_string()),
+                b'l' => aig.latch_symbols.insert(pos, name.to_string()),
+                _ => None,
+            };
+        }
+        Ok(aig)
+    }
+
+    /// Builds the and gates in file order, which is topological for binary
+    /// AIGER. Gates of an ASCII AIGER used before their definition are built
+    /// first by a depth-first search with an explicit stack, where a gate
+    /// met again while it is still on the stack closes a cycle.
+    fn build_ands(
+        &self,
+        tm: &mut TermManager,
+        terms: &mut GHashMap<u32, Term>,
+    ) -> Result<(), ParseError> {
+        let mut ands: GHashMap<u32, &AigerAnd> = GHashMap::new();
+        for and in self.ands.iter() {
+            let v = and.lhs >> 1;
+            if and.lhs & 1 == 1 || v == 0 || terms.contains_key(&v) || ands.insert(v, and).is_some()
+            {
+                return Err(ParseError::syntax(format!(
+                    "invalid aiger and lhs {}",
+                    and.lhs
+                )));
+            }
+        }
+        let mut on_stack = GHashSet::new();
+        let mut stack = Vec::new();
+        for and in self.ands.iter() {
+            if terms.contains_key(&(and.lhs >> 1)) {
+                continue;
+            }
+            on_stack.insert(and.lhs >> 1);
+            stack.push(and);
+            while let Some(&and) = stack.last() {
+                let undef = [and.rhs0 >> 1, and.rhs1 >> 1]
+                    .into_iter()
+                    .find(|v| *v != 0 && !terms.contains_key(v));
+                let Some(v) = undef else {
+                    let t = lit_term(tm, terms, and.rhs0)? & lit_term(tm, terms, and.rhs1)?;
+                    terms.insert(and.lhs >> 1, t);
+                    on_stack.remove(&(and.lhs >> 1));
+                    stack.pop();
+                    continue;
+                };
+                if !on_stack.insert(v) {
+                    return Err(ParseError::syntax("aiger and gates are cyclic"));
+                }
+                let Some(&child) = ands.get(&v) else {
+                    return Err(ParseError::syntax(format!(
+                        "aiger literal {} undefined",
+                        v << 1
+                    )));
+                };
+                stack.push(child);
+            }
+        }
+        Ok(())
+    }
+
+    fn to_transys(&self, tm: &mut TermManager) -> Result<TransitionSys, ParseError> {
+        let mut terms = GHashMap::new();
+        let mut ts = TransitionSys::new();
+        let mut define = |tm: &mut TermManager, lit: u32, symbol: Option<&String>| {
+            if lit & 1 == 1 || lit < 2 || terms.contains_key(&(lit >> 1)) {
+                return Err(ParseError::syntax(format!("invalid aiger literal {lit}")));
+            }
+            let var = new_var(tm, symbol);
+            terms.insert(lit >> 1, var.clone());
+            Ok(var)
+        };
+        for (n, i) in self.inputs.iter().enumerate() {
+            let var = define(tm, *i, self.input_symbols.get(&n))?;
+            ts.input.push(var);
+        }
+        for (n, l) in self.latches.iter().enumerate() {
+            define(tm, l.lit, self.latch_symbols.get(&n))?;
+        }
+        self.build_ands(tm, &mut terms)?;
+        for l in self.latches.iter() {
+            let latch = terms[&(l.lit >> 1)].clone();
+            let init = l.init.map(|i| tm.bool_const(i));
+            let next = lit_term(tm, &terms, l.next)?;
+            ts.add_latch(latch, init, next);
+        }
+        for (lits, sec) in [
+            (&self.outputs, &mut ts.output),
+            (&self.bads, &mut ts.bad),
+            (&self.constraints, &mut ts.constraint),
+        ] {
+            for l in lits.iter() {
+                sec.push(lit_term(tm, &terms, *l)?);
+            }
+        }
+        Ok(ts)
+    }
+}
+
+#[inline]
+fn new_var(tm: &mut TermManager, symbol: Option<&String>) -> Term {
+    match symbol {
+        Some(s) => tm.new_named_var(s.as_str(), Sort::bool()),
+        None => tm.new_var(Sort::bool()),
+    }
+}
+
+#[inline]
+fn lit_term(
+    tm: &mut TermManager,
+    terms: &GHashMap<u32, Term>,
+    lit: u32,
+) -> Result<Term, ParseError> {
+    let var = lit >> 1;
+    let term = if var == 0 {
+        tm.bool_const(false)
+    } else {
+        terms
+            .get(&var)
+            .ok_or_else(|| ParseError::syntax(format!("aiger literal {lit} undefined")))?
+            .clone()
+    };
+    Ok(if lit & 1 == 1 { !term } else { term })
+}
+
+/// Parses an ASCII or binary AIGER, creating a boolean var for each input
+/// and latch named by its symbol.
+pub fn parse_aiger(tm: &mut TermManager, data: &[u8]) -> Result<TransitionSys, ParseError> {
+    Aiger::parse(data)?.to_transys(tm)
+}
+
+pub fn read_aiger(
+    tm: &mut TermManager,
+    path: impl AsRef<Path>,
+) -> Result<TransitionSys, ParseError> {
+    let data = fs::read(path)?;
+    parse_aiger(tm, &data)
+}
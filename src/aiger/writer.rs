@@ -0,0 +1,241 @@
+use super::{Aiger, AigerAnd, AigerLatch};
+use crate::op::{And, Eq, Ite, Not, Or, Xor};
+use crate::{Term, TermType, TransitionSys, support_terms};
+use giputils::hash::{GHashMap, GHashSet};
+use std::fmt::Write;
+use std::ops::Deref;
+
+#[derive(Default)]
+struct AigerBuilder {
+    aig: Aiger,
+    lits: GHashMap<Term, u32>,
+    strash: GHashMap<(u32, u32), u32>,
+}
+
+impl AigerBuilder {
+    #[inline]
+    fn new_var(&mut self, term: &Term) -> u32 {
+        debug_assert!(term.is_bool());
+        self.aig.max_var += 1;
+        let lit = self.aig.max_var * 2;
+        self.lits.insert(term.clone(), lit);
+        lit
+    }
+
+    fn and(&mut self, x: u32, y: u32) -> u32 {
+        let (rhs0, rhs1) = if x > y { (x, y) } else { (y, x) };
+        if rhs1 == 0 || rhs0 == rhs1 ^ 1 {
+            return 0;
+        }
+        if rhs1 == 1 || rhs0 == rhs1 {
+            return rhs0;
+        }
+        if let Some(lit) = self.strash.get(&(rhs0, rhs1)) {
+            return *lit;
+        }
+        self.aig.max_var += 1;
+        let lhs = self.aig.max_var * 2;
+        self.aig.ands.push(AigerAnd { lhs, rhs0, rhs1 });
+        self.strash.insert((rhs0, rhs1), lhs);
+        lhs
+    }
+
+    #[inline]
+    fn or(&mut self, x: u32, y: u32) -> u32 {
+        self.and(x ^ 1, y ^ 1) ^ 1
+    }
+
+    #[inline]
+    fn ite(&mut self, c: u32, t: u32, e: u32) -> u32 {
+        let ct = self.and(c, t);
+        let ce = self.and(c ^ 1, e);
+        self.or(ct, ce)
+    }
+
+    fn lit(&mut self, term: &Term) -> u32 {
+        if let Some(lit) = self.lits.get(term) {
+            return *lit;
+        }
+        debug_assert!(term.is_bool());
+        let lit = match term.deref() {
+            TermType::Const(c) => c.bool().unwrap() as u32,
+            TermType::Var(_) => panic!("{:?} not declared", term),
+            TermType::Op(op) => {
+                let l: Vec<u32> = op.terms.iter().map(|t| self.lit(t)).collect();
+                if op.op == Not {
+                    l[0] ^ 1
+                } else if op.op == And {
+                    self.and(l[0], l[1])
+                } else if op.op == Or {
+                    self.or(l[0], l[1])
+                } else if op.op == Xor || op.op == Eq {
+                    let xor = self.ite(l[0], l[1] ^ 1, l[1]);
+                    if op.op == Eq { xor ^ 1 } else { xor }
+                } else if op.op == Ite {
+                    self.ite(l[0], l[1], l[2])
+                } else {
+                    panic!("{:?} not support aiger, bitblast it first", op.op)
+                }
+            }
+        };
+        self.lits.insert(term.clone(), lit);
+        lit
+    }
+}
+
+impl Aiger {
+    /// Lowers a bit-level transition system, vars that are neither inputs nor
+    /// latches become extra inputs.
+    fn from_transys(ts: &TransitionSys) -> Self {
+        let declared: GHashSet<&Term> = ts.input.iter().chain(ts.latch.iter()).collect();
+        let roots = ts.init.values().chain(ts.next.values());
+        let mut free =
+            support_terms(roots.chain(ts.bad.iter().chain(&ts.constraint).chain(&ts.output)));
+        free.retain(|v| !declared.contains(v));
+        let mut builder = AigerBuilder::default();
+        for (n, i) in ts.input.iter().chain(free.iter()).enumerate() {
+            let lit = builder.new_var(i);
+            builder.aig.inputs.push(lit);
+            if let Some(name) = i.var_name() {
+                builder.aig.input_symbols.insert(n, name);
+            }
+        }
+        let latches: Vec<u32> = ts.latch.iter().map(|l| builder.new_var(l)).collect();
+        for (n, l) in ts.latch.iter().enumerate() {
+            if let Some(name) = l.var_name() {
+                builder.aig.latch_symbols.insert(n, name);
+            }
+        }
+        for (l, lit) in ts.latch.iter().zip(latches) {
+            let next = ts
+                .next
+                .get(l)
+                .unwrap_or_else(|| panic!("latch {:?} without next", l));
+            let next = builder.lit(next);
+            let init = ts.init.get(l).map(|i| {
+                i.try_bv_const()
+                    .and_then(|c| c.bool())
+                    .unwrap_or_else(|| panic!("aiger not support non-constant init {:?}", i))
+            });
+            builder.aig.latches.push(AigerLatch { lit, next, init });
+        }
+        for o in ts.output.iter() {
+            let lit = builder.lit(o);
+            builder.aig.outputs.push(lit);
+        }
+        for b in ts.bad.iter() {
+            let lit = builder.lit(b);
+            builder.aig.bads.push(lit);
+        }
+        for c in ts.constraint.iter() {
+            let lit = builder.lit(c);
+            builder.aig.constraints.push(lit);
+        }
+        builder.aig
+    }
+
+    fn header(&self, format: &str) -> String {
+        let mut header = format!(
+            "{format} {} {} {} {} {}",
+            self.max_var,
+            self.inputs.len(),
+            self.latches.len(),
+            self.outputs.len(),
+            self.ands.len()
+        );
+        if !self.bads.is_empty() || !self.constraints.is_empty() {
+            write!(header, " {} {}", self.bads.len(), self.constraints.len()).unwrap();
+        }
+        header.push('\n');
+        header
+    }
+
+    /// The latch and property section shared by both formats, latch lines
+    /// omitting their literal in the binary format.
+    fn write_sections(&self, out: &mut String, binary: bool) {
+        for l in self.latches.iter() {
+            if !binary {
+                write!(out, "{} ", l.lit).unwrap();
+            }
+            write!(out, "{}", l.next).unwrap();
+            match l.init {
+                Some(false) => (),
+                Some(true) => out.push_str(" 1"),
+                None => write!(out, " {}", l.lit).unwrap(),
+            }
+            out.push('\n');
+        }
+        for lit in self
+            .outputs
+            .iter()
+            .chain(&self.bads)
+            .chain(&self.constraints)
+        {
+            writeln!(out, "{lit}").unwrap();
+        }
+    }
+
+    /// The symbol table, names containing a newline are dropped.
+    fn write_symbols(&self, out: &mut String) {
+        for (kind, symbols, len) in [
+            ('i', &self.input_symbols, self.inputs.len()),
+            ('l', &self.latch_symbols, self.latches.len()),
+        ] {
+            for n in 0..len {
+                if let Some(name) = symbols.get(&n).filter(|s| !s.contains('\n')) {
+                    writeln!(out, "{kind}{n} {name}").unwrap();
+                }
+            }
+        }
+    }
+
+    fn to_aag(&self) -> String {
+        let mut out = self.header("aag");
+        for i in self.inputs.iter() {
+            writeln!(out, "{i}").unwrap();
+        }
+        self.write_sections(&mut out, false);
+        for a in self.ands.iter() {
+            writeln!(out, "{} {} {}", a.lhs, a.rhs0, a.rhs1).unwrap();
+        }
+        self.write_symbols(&mut out);
+        out
+    }
+
+    fn to_aig(&self) -> Vec<u8> {
+        debug_assert!(
+            self.inputs
+                .iter()
+                .enumerate()
+                .all(|(i, l)| *l == 2 * (i as u32 + 1))
+        );
+        let mut out = self.header("aig");
+        self.write_sections(&mut out, true);
+        let mut out = out.into_bytes();
+        for a in self.ands.iter() {
+            for mut delta in [a.lhs - a.rhs0, a.rhs0 - a.rhs1] {
+                while delta & !0x7f != 0 {
+                    out.push((delta & 0x7f) as u8 | 0x80);
+                    delta >>= 7;
+                }
+                out.push(delta as u8);
+            }
+        }
+        let mut symbols = String::new();
+        self.write_symbols(&mut symbols);
+        out.extend_from_slice(symbols.as_bytes());
+        out
+    }
+}
+
+/// Dumps a bit-level transition system, see [`TransitionSys::bitblast`], as
+/// ASCII AIGER.
+pub fn dump_aag(ts: &TransitionSys) -> String {
+    Aiger::from_transys(ts).to_aag()
+}
+
+/// Dumps a bit-level transition system, see [`TransitionSys::bitblast`], as
+/// binary AIGER.
+pub fn dump_aig(ts: &TransitionSys) -> Vec<u8> {
+    Aiger::from_transys(ts).to_aig()
+}
@@ -1,7 +1,56 @@
-use crate::{BvConst, Sort, Term, TermManager, TermType, TermVec};
+use crate::{
+    BvConst, OpTerm, PostOrder, Sort, Term, TermFold, TermManager, TermType, TermVec, TransitionSys,
+};
 use giputils::hash::GHashMap;
-use logic_form::{DagCnf, Lit};
-use std::{iter::repeat_with, ops::Deref};
+use logic_form::{DagCnf, Lit, LitVec};
+use std::{
+    iter::repeat_with,
+    ops::{Deref, Not},
+};
+
+/// The polarities in which a term occurs, deciding which directions of its
+/// definition the polarity-aware CNF encoding emits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Polarity {
+    Positive,
+    Negative,
+    Both,
+}
+
+impl Polarity {
+    #[inline]
+    pub fn positive(self) -> bool {
+        self != Polarity::Negative
+    }
+
+    #[inline]
+    pub fn negative(self) -> bool {
+        self != Polarity::Positive
+    }
+
+    #[inline]
+    pub fn contains(self, other: Polarity) -> bool {
+        self == other || self == Polarity::Both
+    }
+
+    #[inline]
+    pub fn merge(self, other: Polarity) -> Polarity {
+        if self == other { self } else { Polarity::Both }
+    }
+}
+
+impl Not for Polarity {
+    type Output = Polarity;
+
+    #[inline]
+    fn not(self) -> Self::Output {
+        match self {
+            Polarity::Positive => Polarity::Negative,
+            Polarity::Negative => Polarity::Positive,
+            Polarity::Both => Polarity::Both,
+        }
+    }
+}
 
 impl BvConst {
     #[inline]
@@ -14,57 +63,232 @@ impl BvConst {
         debug_assert!(self.len() == 1);
         Lit::constant(self.c[0])
     }
+
+    #[inline]
+    pub fn bv_cnf_encode(&self) -> LitVec {
+        self.c.iter().map(|c| Lit::constant(*c)).collect()
+    }
 }
 
 pub fn var_bitblast(tm: &mut TermManager, sort: Sort) -> TermVec {
-    let size = match sort {
-        Sort::Bv(s) => s,
-        Sort::Array(i, e) => {
-            let shifted = 1usize.checked_shl(i as u32).unwrap();
-            shifted.checked_mul(e).unwrap()
-        }
-    };
     repeat_with(|| tm.new_var(Sort::bool()))
-        .take(size)
+        .take(sort.size())
         .collect()
 }
 
-impl Term {
-    pub fn bitblast(&self, tm: &mut TermManager, map: &mut GHashMap<Term, TermVec>) -> TermVec {
-        if let Some(res) = map.get(self) {
-            return res.clone();
+struct Bitblaster<'a> {
+    tm: &'a mut TermManager,
+}
+
+impl TermFold for Bitblaster<'_> {
+    type Output = TermVec;
+
+    #[inline]
+    fn fold_const(&mut self, _term: &Term, c: &BvConst) -> TermVec {
+        c.bitblast(self.tm)
+    }
+
+    fn fold_var(&mut self, term: &Term, _v: u32) -> TermVec {
+        let bits = var_bitblast(self.tm, term.sort());
+        if let Some(name) = term.var_name() {
+            for (i, b) in bits.iter().enumerate() {
+                self.tm.set_var_name(b, format!("{name}[{i}]"));
+            }
         }
-        let blast = match self.deref() {
-            TermType::Const(const_term) => const_term.bitblast(tm),
-            TermType::Var(_) => var_bitblast(tm, self.sort()),
+        bits
+    }
+
+    #[inline]
+    fn fold_op(&mut self, _term: &Term, op: &OpTerm, terms: &[TermVec]) -> TermVec {
+        op.op.bitblast(self.tm, terms)
+    }
+}
+
+struct CnfEncoder<'a> {
+    dc: &'a mut DagCnf,
+}
+
+impl TermFold for CnfEncoder<'_> {
+    type Output = Lit;
+
+    #[inline]
+    fn fold_const(&mut self, _term: &Term, c: &BvConst) -> Lit {
+        c.cnf_encode()
+    }
+
+    #[inline]
+    fn fold_var(&mut self, _term: &Term, _v: u32) -> Lit {
+        self.dc.new_var().lit()
+    }
+
+    #[inline]
+    fn fold_op(&mut self, _term: &Term, op: &OpTerm, terms: &[Lit]) -> Lit {
+        op.op.cnf_encode(self.dc, terms)
+    }
+}
+
+struct BvCnfEncoder<'a> {
+    dc: &'a mut DagCnf,
+}
+
+impl TermFold for BvCnfEncoder<'_> {
+    type Output = LitVec;
+
+    #[inline]
+    fn fold_const(&mut self, _term: &Term, c: &BvConst) -> LitVec {
+        c.bv_cnf_encode()
+    }
+
+    #[inline]
+    fn fold_var(&mut self, term: &Term, _v: u32) -> LitVec {
+        repeat_with(|| self.dc.new_var().lit())
+            .take(term.sort().size())
+            .collect()
+    }
+
+    #[inline]
+    fn fold_op(&mut self, _term: &Term, op: &OpTerm, terms: &[LitVec]) -> LitVec {
+        op.op.bv_cnf_encode(self.dc, terms)
+    }
+}
+
+/// Collects the polarities in which each term occurs below `roots`. A term
+/// already encoded in `map` in a polarity not covering a new one has to be
+/// encoded again in both, and so do its operands.
+fn collect_polarity<'a>(
+    roots: impl IntoIterator<Item = &'a Term>,
+    polarity: Polarity,
+    map: &GHashMap<Term, (Lit, Polarity)>,
+    pmap: &mut GHashMap<Term, Polarity>,
+) {
+    let mut worklist: Vec<(Term, Polarity)> =
+        roots.into_iter().map(|r| (r.clone(), polarity)).collect();
+    while let Some((term, polarity)) = worklist.pop() {
+        let polarity = match pmap.get(&term) {
+            Some(p) if p.contains(polarity) => continue,
+            Some(p) => p.merge(polarity),
+            None => polarity,
+        };
+        let (polarity, encoded) = match map.get(&term) {
+            Some((_, p)) if p.contains(polarity) => (polarity, true),
+            Some((_, p)) => (p.merge(polarity), false),
+            None => (polarity, false),
+        };
+        pmap.insert(term.clone(), polarity);
+        if encoded {
+            continue;
+        }
+        if let TermType::Op(op_term) = term.deref() {
+            for (i, s) in op_term.terms.iter().enumerate() {
+                worklist.push((s.clone(), op_term.op.operand_polarity(polarity, i)));
+            }
+        }
+    }
+}
+
+/// Encodes `roots` in the polarities of `pmap`, skipping the terms already
+/// encoded in a polarity covering theirs.
+fn pg_cnf_encode<'a>(
+    roots: impl IntoIterator<Item = &'a Term>,
+    dc: &mut DagCnf,
+    pmap: &GHashMap<Term, Polarity>,
+    map: &mut GHashMap<Term, (Lit, Polarity)>,
+) {
+    let order: Vec<Term> = PostOrder::new(roots, |t| {
+        map.get(t).is_some_and(|(_, p)| p.contains(pmap[t]))
+    })
+    .collect();
+    for term in order {
+        let polarity = match map.get(&term) {
+            Some((_, p)) => p.merge(pmap[&term]),
+            None => pmap[&term],
+        };
+        let (blast, polarity) = match term.deref() {
+            TermType::Const(const_term) => (const_term.cnf_encode(), Polarity::Both),
+            TermType::Var(_) => (dc.new_var().lit(), Polarity::Both),
             TermType::Op(op_term) => {
-                let terms: Vec<TermVec> =
-                    op_term.terms.iter().map(|s| s.bitblast(tm, map)).collect();
-                op_term.op.bitblast(tm, &terms)
+                let terms: Vec<Lit> = op_term.terms.iter().map(|s| map[s].0).collect();
+                let l = op_term.op.cnf_encode_polarity(dc, &terms, polarity);
+                (l, polarity)
             }
         };
-        map.insert(self.clone(), blast.clone());
-        map.get(self).unwrap().clone()
+        map.insert(term, (blast, polarity));
+    }
+}
+
+impl Term {
+    #[inline]
+    pub fn bitblast(&self, tm: &mut TermManager, map: &mut GHashMap<Term, TermVec>) -> TermVec {
+        self.fold(&mut Bitblaster { tm }, map)
     }
 
+    #[inline]
     pub fn cnf_encode(&self, dc: &mut DagCnf, map: &mut GHashMap<Term, Lit>) -> Lit {
-        if let Some(res) = map.get(self) {
-            return *res;
+        self.fold(&mut CnfEncoder { dc }, map)
+    }
+
+    /// Plaisted-Greenbaum variant of [`Term::cnf_encode`], each op term only
+    /// gets the directions of its definition required by the polarities in
+    /// which it occurs below a root of the given `polarity`. A term met again
+    /// in a polarity not yet encoded gets a fresh literal defined in both, so
+    /// alternating calls encode each cone at most twice.
+    pub fn cnf_encode_polarity(
+        &self,
+        dc: &mut DagCnf,
+        polarity: Polarity,
+        map: &mut GHashMap<Term, (Lit, Polarity)>,
+    ) -> Lit {
+        let mut pmap = GHashMap::new();
+        collect_polarity([self], polarity, map, &mut pmap);
+        pg_cnf_encode([self], dc, &pmap, map);
+        map[self].0
+    }
+
+    /// Encodes a term of any sort directly into clauses, one literal per bit
+    /// with the least significant bit first.
+    #[inline]
+    pub fn bv_cnf_encode(&self, dc: &mut DagCnf, map: &mut GHashMap<Term, LitVec>) -> LitVec {
+        self.fold(&mut BvCnfEncoder { dc }, map)
+    }
+}
+
+impl TransitionSys {
+    /// Bitblasts the system into one whose inputs, latches and terms are all
+    /// single-bit.
+    pub fn bitblast(&self, tm: &mut TermManager) -> TransitionSys {
+        let mut map = GHashMap::new();
+        let mut ts = TransitionSys::new();
+        for i in self.input.iter() {
+            ts.input.extend(i.bitblast(tm, &mut map));
         }
-        let blast = match self.deref() {
-            TermType::Const(const_term) => const_term.cnf_encode(),
-            TermType::Var(_) => dc.new_var().lit(),
-            TermType::Op(op_term) => {
-                let terms: Vec<Lit> = op_term
-                    .terms
-                    .iter()
-                    .map(|s| s.cnf_encode(dc, map))
-                    .collect();
-                op_term.op.cnf_encode(dc, &terms)
+        let latch: Vec<TermVec> = self
+            .latch
+            .iter()
+            .map(|l| l.bitblast(tm, &mut map))
+            .collect();
+        for (l, bits) in self.latch.iter().zip(latch) {
+            let init = self.init.get(l).map(|i| i.bitblast(tm, &mut map));
+            let next = self.next.get(l).map(|n| n.bitblast(tm, &mut map));
+            for (i, b) in bits.into_iter().enumerate() {
+                if let Some(init) = &init {
+                    ts.init.insert(b.clone(), init[i].clone());
+                }
+                if let Some(next) = &next {
+                    ts.next.insert(b.clone(), next[i].clone());
+                }
+                ts.latch.push(b);
             }
-        };
-        map.insert(self.clone(), blast);
-        *map.get(self).unwrap()
+        }
+        for b in self.bad.iter() {
+            ts.bad.push(b.bitblast(tm, &mut map).item());
+        }
+        for c in self.constraint.iter() {
+            ts.constraint.push(c.bitblast(tm, &mut map).item());
+        }
+        for o in self.output.iter() {
+            ts.output.extend(o.bitblast(tm, &mut map));
+        }
+        ts
     }
 }
 
@@ -83,3 +307,24 @@ pub fn cnf_encode_terms<'a, I: IntoIterator<Item = &'a Term>>(
 ) -> impl Iterator<Item = Lit> {
     terms.into_iter().map(|t| t.cnf_encode(dc, map))
 }
+
+pub fn bv_cnf_encode_terms<'a, I: IntoIterator<Item = &'a Term>>(
+    terms: I,
+    dc: &mut DagCnf,
+    map: &mut GHashMap<Term, LitVec>,
+) -> impl Iterator<Item = LitVec> {
+    terms.into_iter().map(|t| t.bv_cnf_encode(dc, map))
+}
+
+pub fn cnf_encode_terms_polarity<'a, I: IntoIterator<Item = &'a Term>>(
+    terms: I,
+    dc: &mut DagCnf,
+    polarity: Polarity,
+    map: &mut GHashMap<Term, (Lit, Polarity)>,
+) -> Vec<Lit> {
+    let terms: Vec<&Term> = terms.into_iter().collect();
+    let mut pmap = GHashMap::new();
+    collect_polarity(terms.iter().copied(), polarity, map, &mut pmap);
+    pg_cnf_encode(terms.iter().copied(), dc, &pmap, map);
+    terms.into_iter().map(|t| map[t].0).collect()
+}
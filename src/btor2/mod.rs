@@ -0,0 +1,5 @@
+mod reader;
+mod writer;
+
+pub use reader::*;
+pub use writer::*;
@@ -0,0 +1,229 @@
+use crate::op::DynOp;
+use crate::{BvConst, ParseError, Sort, Term, TermManager, TransitionSys};
+use giputils::hash::GHashMap;
+use std::{fs, path::Path};
+
+struct Btor2Reader<'a> {
+    tm: &'a mut TermManager,
+    sorts: GHashMap<usize, Sort>,
+    nodes: GHashMap<usize, Term>,
+    ts: TransitionSys,
+}
+
+impl Btor2Reader<'_> {
+    #[inline]
+    fn num(tok: &str) -> Result<usize, ParseError> {
+        tok.parse()
+            .map_err(|_| ParseError::syntax(format!("invalid btor2 number {tok}")))
+    }
+
+    #[inline]
+    fn sort(&self, tok: &str) -> Result<Sort, ParseError> {
+        let id = Self::num(tok)?;
+        self.sorts
+            .get(&id)
+            .copied()
+            .ok_or_else(|| ParseError::syntax(format!("undefined btor2 sort {id}")))
+    }
+
+    #[inline]
+    fn bv_sort(&self, tok: &str) -> Result<usize, ParseError> {
+        match self.sort(tok)? {
+            Sort::Bv(w) => Ok(w),
+            s => Err(ParseError::syntax(format!(
+                "expected btor2 bit-vector sort, found {s:?}"
+            ))),
+        }
+    }
+
+    #[inline]
+    fn node(&self, tok: &str) -> Result<Term, ParseError> {
+        let (neg, tok) = match tok.strip_prefix('-') {
+            Some(tok) => (true, tok),
+            None => (false, tok),
+        };
+        let id = Self::num(tok)?;
+        let node = self
+            .nodes
+            .get(&id)
+            .ok_or_else(|| ParseError::syntax(format!("undefined btor2 node {id}")))?;
+        if neg && node.sort().is_array() {
+            return Err(ParseError::syntax(format!("negated btor2 array node {id}")));
+        }
+        Ok(if neg { !node } else { node.clone() })
+    }
+
+    #[inline]
+    fn var(&mut self, sort: &str, symbol: Option<&&str>) -> Result<Term, ParseError> {
+        let sort = self.sort(sort)?;
+        Ok(match symbol {
+            Some(s) => self.tm.new_named_var(*s, sort),
+            None => self.tm.new_var(sort),
+        })
+    }
+
+    fn line(&mut self, line: &str) -> Result<(), ParseError> {
+        let tok: Vec<&str> = line.split_whitespace().collect();
+        let arg = |i: usize| {
+            tok.get(i)
+                .copied()
+                .ok_or_else(|| ParseError::syntax("missing btor2 argument"))
+        };
+        let id = Self::num(tok[0])?;
+        let node = match arg(1)? {
+            "sort" => {
+                let sort = match arg(2)? {
+                    "bitvec" => match Self::num(arg(3)?)? {
+                        0 => return Err(ParseError::syntax("btor2 bit-vector sort of width 0")),
+                        w => Sort::Bv(w),
+                    },
+                    "array" => Sort::Array(self.bv_sort(arg(3)?)?, self.bv_sort(arg(4)?)?),
+                    s => return Err(ParseError::unsupported(format!("btor2 sort {s}"))),
+                };
+                self.sorts.insert(id, sort);
+                return Ok(());
+            }
+            "input" => {
+                let var = self.var(arg(2)?, tok.get(3))?;
+                self.ts.input.push(var.clone());
+                var
+            }
+            "state" => {
+                let var = self.var(arg(2)?, tok.get(3))?;
+                self.ts.latch.push(var.clone());
+                var
+            }
+            "init" | "next" => {
+                let (state, value) = (self.node(arg(3)?)?, self.node(arg(4)?)?);
+                if state.try_var_term().is_none() || state.sort() != value.sort() {
+                    return Err(ParseError::syntax(format!(
+                        "btor2 {} of {:?} by {:?}",
+                        tok[1],
+                        state.sort(),
+                        value.sort()
+                    )));
+                }
+                if tok[1] == "init" {
+                    self.ts.init.insert(state, value);
+                } else {
+                    self.ts.next.insert(state, value);
+                }
+                return Ok(());
+            }
+            "bad" | "constraint" | "output" => {
+                let node = self.node(arg(2)?)?;
+                match tok[1] {
+                    "bad" => self.ts.bad.push(node),
+                    "constraint" => self.ts.constraint.push(node),
+                    _ => self.ts.output.push(node),
+                }
+                return Ok(());
+            }
+            "justice" | "fair" => {
+                return Err(ParseError::unsupported(format!(
+                    "btor2 liveness property {}",
+                    tok[1]
+                )));
+            }
+            "const" | "constd" | "consth" => {
+                let width = self.bv_sort(arg(2)?)?;
+                let value = arg(3)?;
+                let digits = value.strip_prefix('-').filter(|_| tok[1] == "constd");
+                let valid = match tok[1] {
+                    "const" => value.bytes().all(|c| c == b'0' || c == b'1'),
+                    "constd" => digits.unwrap_or(value).bytes().all(|c| c.is_ascii_digit()),
+                    _ => value.bytes().all(|c| c.is_ascii_hexdigit()),
+                };
+                if !valid || digits.unwrap_or(value).is_empty() {
+                    return Err(ParseError::syntax(format!(
+                        "invalid btor2 {} {value}",
+                        tok[1]
+                    )));
+                }
+                let c = match tok[1] {
+                    "const" => BvConst::from_binary(value, width),
+                    "constd" => BvConst::from_decimal(value, width),
+                    _ => BvConst::from_hex(value, width),
+                };
+                self.tm.bv_const(c)
+            }
+            "zero" => self.tm.bv_const_zero(self.bv_sort(arg(2)?)?),
+            "one" => self.tm.bv_const_one(self.bv_sort(arg(2)?)?),
+            "ones" => self.tm.bv_const_ones(self.bv_sort(arg(2)?)?),
+            "sext" | "uext" | "slice" => {
+                let x = self.node(arg(3)?)?;
+                if x.sort().is_array() {
+                    return Err(ParseError::syntax(format!("btor2 {} of an array", tok[1])));
+                }
+                match tok[1] {
+                    "sext" => x.sext(Self::num(arg(4)?)?),
+                    "uext" => x.uext(Self::num(arg(4)?)?),
+                    _ => {
+                        let (hi, lo) = (Self::num(arg(4)?)?, Self::num(arg(5)?)?);
+                        if lo > hi || hi >= x.bv_len() {
+                            return Err(ParseError::syntax(format!(
+                                "btor2 slice {hi} {lo} of width {}",
+                                x.bv_len()
+                            )));
+                        }
+                        x.slice(hi, lo)
+                    }
+                }
+            }
+            op => {
+                let op = DynOp::from_name(op)
+                    .ok_or_else(|| ParseError::unsupported(format!("btor2 op {op}")))?;
+                let args = (3..3 + op.num_operand())
+                    .map(|i| self.node(arg(i)?))
+                    .collect::<Result<Vec<Term>, _>>()?;
+                if !op.well_sorted(&args) {
+                    return Err(ParseError::syntax(format!(
+                        "ill-sorted btor2 op {}",
+                        tok[1]
+                    )));
+                }
+                self.tm.new_op_term(op, &args)
+            }
+        };
+        let sort = self.sort(arg(2)?)?;
+        if node.sort() != sort {
+            return Err(ParseError::syntax(format!(
+                "btor2 node of sort {:?} declared as {sort:?}",
+                node.sort()
+            )));
+        }
+        self.nodes.insert(id, node);
+        Ok(())
+    }
+}
+
+/// Parses a BTOR2 model, creating a var for each input and state named by
+/// its symbol. Liveness properties are reported as unsupported.
+pub fn parse_btor2(tm: &mut TermManager, src: &str) -> Result<TransitionSys, ParseError> {
+    let mut reader = Btor2Reader {
+        tm,
+        sorts: GHashMap::new(),
+        nodes: GHashMap::new(),
+        ts: TransitionSys::new(),
+    };
+    for (n, line) in src.lines().enumerate() {
+        let line = line.split(';').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        reader.line(line).map_err(|e| match e {
+            ParseError::Syntax(m) => ParseError::Syntax(format!("line {}: {m}", n + 1)),
+            ParseError::Unsupported(m) => ParseError::Unsupported(format!("line {}: {m}", n + 1)),
+            e => e,
+        })?;
+    }
+    Ok(reader.ts)
+}
+
+pub fn read_btor2(
+    tm: &mut TermManager,
+    path: impl AsRef<Path>,
+) -> Result<TransitionSys, ParseError> {
+    let src = fs::read_to_string(path)?;
+    parse_btor2(tm, &src)
+}
@@ -0,0 +1,124 @@
+use crate::op::{Sext, Slice};
+use crate::{Sort, Term, TermType, TransitionSys};
+use giputils::hash::GHashMap;
+use std::fmt::Write;
+use std::ops::Deref;
+
+#[derive(Default)]
+struct Btor2Writer {
+    sorts: GHashMap<Sort, usize>,
+    nodes: GHashMap<Term, usize>,
+    num_node: usize,
+    out: String,
+}
+
+impl Btor2Writer {
+    #[inline]
+    fn emit(&mut self, line: &str) -> usize {
+        self.num_node += 1;
+        writeln!(self.out, "{} {line}", self.num_node).unwrap();
+        self.num_node
+    }
+
+    fn sort(&mut self, sort: Sort) -> usize {
+        if let Some(id) = self.sorts.get(&sort) {
+            return *id;
+        }
+        let line = match sort {
+            Sort::Bv(w) => format!("sort bitvec {w}"),
+            Sort::Array(i, e) => {
+                let i = self.sort(Sort::Bv(i));
+                let e = self.sort(Sort::Bv(e));
+                format!("sort array {i} {e}")
+            }
+        };
+        let id = self.emit(&line);
+        self.sorts.insert(sort, id);
+        id
+    }
+
+    fn var(&mut self, var: &Term, kind: &str) -> usize {
+        debug_assert!(!self.nodes.contains_key(var));
+        let sid = self.sort(var.sort());
+        let line = match var.var_name() {
+            Some(name) if !name.contains(char::is_whitespace) && !name.contains(';') => {
+                format!("{kind} {sid} {name}")
+            }
+            _ => format!("{kind} {sid}"),
+        };
+        let id = self.emit(&line);
+        self.nodes.insert(var.clone(), id);
+        id
+    }
+
+    fn node(&mut self, term: &Term) -> usize {
+        if let Some(id) = self.nodes.get(term) {
+            return *id;
+        }
+        let line = match term.deref() {
+            TermType::Const(c) => {
+                let sid = self.sort(term.sort());
+                format!("const {sid} {c:b}")
+            }
+            TermType::Var(_) => return self.var(term, "input"),
+            TermType::Op(op) => {
+                let args: Vec<usize> = op.terms.iter().map(|t| self.node(t)).collect();
+                let sid = self.sort(term.sort());
+                if let Some(Slice { hi, lo }) = op.op.downcast_ref::<Slice>() {
+                    format!("slice {sid} {} {hi} {lo}", args[0])
+                } else if let Some(Sext(ext)) = op.op.downcast_ref::<Sext>() {
+                    format!("sext {sid} {} {ext}", args[0])
+                } else {
+                    let mut line = format!("{} {sid}", op.op.name().to_lowercase());
+                    for a in args {
+                        write!(line, " {a}").unwrap();
+                    }
+                    line
+                }
+            }
+        };
+        let id = self.emit(&line);
+        self.nodes.insert(term.clone(), id);
+        id
+    }
+
+    fn property(&mut self, kind: &str, terms: &[Term]) {
+        for t in terms {
+            let id = self.node(t);
+            self.emit(&format!("{kind} {id}"));
+        }
+    }
+}
+
+/// Dumps a transition system as BTOR2, each distinct term becoming one node.
+pub fn dump_btor2(ts: &TransitionSys) -> String {
+    let mut writer = Btor2Writer::default();
+    for i in ts.input.iter() {
+        writer.var(i, "input");
+    }
+    for l in ts.latch.iter() {
+        writer.var(l, "state");
+    }
+    for (kind, map) in [("init", &ts.init), ("next", &ts.next)] {
+        for l in ts.latch.iter() {
+            if let Some(v) = map.get(l) {
+                let v = writer.node(v);
+                let sid = writer.sort(l.sort());
+                let lid = writer.nodes[l];
+                writer.emit(&format!("{kind} {sid} {lid} {v}"));
+            }
+        }
+    }
+    writer.property("bad", &ts.bad);
+    writer.property("constraint", &ts.constraint);
+    writer.property("output", &ts.output);
+    writer.out
+}
+
+/// Dumps a term DAG as BTOR2 with an `output` per root, its vars becoming
+/// inputs.
+pub fn dump_btor2_terms(roots: &[Term]) -> String {
+    let mut writer = Btor2Writer::default();
+    writer.property("output", roots);
+    writer.out
+}
@@ -0,0 +1,50 @@
+use std::fmt::{self, Display};
+use std::io;
+
+/// An error reading a model from SMT-LIB2, BTOR2 or AIGER.
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    /// The input is malformed.
+    Syntax(String),
+    /// The input is well-formed but uses a feature we do not support.
+    Unsupported(String),
+}
+
+impl ParseError {
+    #[inline]
+    pub(crate) fn syntax(msg: impl Into<String>) -> Self {
+        Self::Syntax(msg.into())
+    }
+
+    #[inline]
+    pub(crate) fn unsupported(msg: impl Into<String>) -> Self {
+        Self::Unsupported(msg.into())
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "io error: {e}"),
+            ParseError::Syntax(m) => write!(f, "syntax error: {m}"),
+            ParseError::Unsupported(m) => write!(f, "unsupported: {m}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    #[inline]
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{BvConst, OpTerm, Term, TermFold};
+use giputils::hash::GHashMap;
+
+struct Evaluator<'a, F: Fn(&Term) -> BvConst> {
+    assignment: &'a F,
+}
+
+impl<F: Fn(&Term) -> BvConst> TermFold for Evaluator<'_, F> {
+    type Output = BvConst;
+
+    #[inline]
+    fn fold_const(&mut self, _term: &Term, c: &BvConst) -> BvConst {
+        c.clone()
+    }
+
+    #[inline]
+    fn fold_var(&mut self, term: &Term, _v: u32) -> BvConst {
+        let v = (self.assignment)(term);
+        debug_assert!(v.len() == term.sort().size());
+        v
+    }
+
+    #[inline]
+    fn fold_op(&mut self, _term: &Term, op: &OpTerm, terms: &[BvConst]) -> BvConst {
+        op.op.eval(terms)
+    }
+}
+
+impl Term {
+    /// Evaluates the term with each var valued by `assignment`. Array values
+    /// are flattened, element `j` of an `Array(i, e)` occupying bits
+    /// `e * j..e * (j + 1)`.
+    #[inline]
+    pub fn eval(
+        &self,
+        assignment: &impl Fn(&Term) -> BvConst,
+        map: &mut GHashMap<Term, BvConst>,
+    ) -> BvConst {
+        self.fold(&mut Evaluator { assignment }, map)
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Model {
+    values: GHashMap<Term, BvConst>,
+}
+
+impl Model {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, var: Term, val: BvConst) {
+        debug_assert!(var.try_var_term().is_some());
+        debug_assert!(var.sort().size() == val.len());
+        self.values.insert(var, val);
+    }
+
+    #[inline]
+    pub fn get(&self, var: &Term) -> Option<&BvConst> {
+        self.values.get(var)
+    }
+
+    /// Evaluates `term` under the model, vars without a value are zero.
+    #[inline]
+    pub fn eval(&self, term: &Term, map: &mut GHashMap<Term, BvConst>) -> BvConst {
+        let assignment = |v: &Term| {
+            self.values
+                .get(v)
+                .cloned()
+                .unwrap_or_else(|| BvConst::new(&vec![false; v.sort().size()]))
+        };
+        term.eval(&assignment, map)
+    }
+}
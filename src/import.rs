@@ -0,0 +1,54 @@
+use crate::{BvConst, OpTerm, Term, TermManager, TermMap, TermVec};
+use giputils::hash::GHashMap;
+
+struct Importer<'a> {
+    tm: &'a mut TermManager,
+}
+
+impl TermMap for Importer<'_> {
+    #[inline]
+    fn map_const(&mut self, _term: &Term, c: &BvConst) -> Term {
+        self.tm.bv_const(c.clone())
+    }
+
+    fn map_var(&mut self, term: &Term, _v: u32) -> Term {
+        let var = self.tm.new_var(term.sort());
+        let info = term.get_manager().var_info(term);
+        if let Some(name) = info.name() {
+            self.tm.set_var_name(&var, name);
+        }
+        self.tm.update_var_info(&var, |i| {
+            i.frame = info.frame;
+            i.attrs = info.attrs;
+        });
+        var
+    }
+
+    #[inline]
+    fn map_op(&mut self, _term: &Term, op: &OpTerm, terms: &[Term]) -> Term {
+        self.tm.new_op_term(op.op.clone(), terms)
+    }
+}
+
+impl TermManager {
+    /// Rebuilds `term` of another manager in this one. `map` maps terms of
+    /// the other manager to their copies here: vars may be mapped by the
+    /// caller beforehand, the other vars get fresh vars with the same name,
+    /// frame and attributes. It memoizes every copy and may be shared between
+    /// calls importing from the same manager.
+    pub fn import(&mut self, term: &Term, map: &mut GHashMap<Term, Term>) -> Term {
+        if term.get_manager() == *self {
+            return term.clone();
+        }
+        term.map(&mut Importer { tm: self }, map)
+    }
+
+    #[inline]
+    pub fn import_terms<'a>(
+        &mut self,
+        terms: impl IntoIterator<Item = &'a Term>,
+        map: &mut GHashMap<Term, Term>,
+    ) -> TermVec {
+        terms.into_iter().map(|t| self.import(t, map)).collect()
+    }
+}
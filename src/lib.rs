@@ -1,13 +1,30 @@
 #![feature(new_range_api, try_trait_v2)]
 
+pub mod aiger;
 pub mod bitblast;
+pub mod btor2;
+mod error;
+mod eval;
+mod import;
 pub mod op;
 mod replace;
 mod simplify;
+pub mod smtlib;
 mod sort;
+mod support;
 mod term;
+mod transys;
+mod unroll;
 mod utils;
+mod visit;
 
+pub use error::ParseError;
+pub use eval::Model;
+pub use simplify::RewriteStats;
 pub use sort::*;
+pub use support::*;
 pub use term::*;
+pub use transys::*;
+pub use unroll::*;
 pub use utils::*;
+pub use visit::*;
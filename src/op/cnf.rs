@@ -0,0 +1,340 @@
+use logic_form::{DagCnf, Lit, LitVec, LitVvec};
+
+#[inline]
+fn lit_if(l: Lit, p: bool) -> Lit {
+    if p { l } else { !l }
+}
+
+#[inline]
+fn is_const(l: Lit) -> bool {
+    l == Lit::constant(false) || l == Lit::constant(true)
+}
+
+#[inline]
+fn new_rel(dc: &mut DagCnf, rel: impl FnOnce(Lit) -> LitVvec) -> Lit {
+    let l = dc.new_var().lit();
+    dc.add_rel(l.var(), &rel(l));
+    l
+}
+
+pub fn cnf_and(dc: &mut DagCnf, x: Lit, y: Lit) -> Lit {
+    let f = Lit::constant(false);
+    if x == f || y == f || x == !y {
+        return f;
+    }
+    if x == !f || x == y {
+        return y;
+    }
+    if y == !f {
+        return x;
+    }
+    new_rel(dc, |l| LitVvec::cnf_and(l, &[x, y]))
+}
+
+pub fn cnf_and_n(dc: &mut DagCnf, xs: &[Lit]) -> Lit {
+    let f = Lit::constant(false);
+    if xs.contains(&f) {
+        return f;
+    }
+    let xs: Vec<Lit> = xs.iter().copied().filter(|l| *l != !f).collect();
+    match xs.len() {
+        0 => !f,
+        1 => xs[0],
+        2 => cnf_and(dc, xs[0], xs[1]),
+        _ => new_rel(dc, |l| LitVvec::cnf_and(l, &xs)),
+    }
+}
+
+#[inline]
+pub fn cnf_or(dc: &mut DagCnf, x: Lit, y: Lit) -> Lit {
+    !cnf_and(dc, !x, !y)
+}
+
+#[inline]
+pub fn cnf_or_n(dc: &mut DagCnf, xs: &[Lit]) -> Lit {
+    let nxs: Vec<Lit> = xs.iter().map(|l| !*l).collect();
+    !cnf_and_n(dc, &nxs)
+}
+
+pub fn cnf_xor(dc: &mut DagCnf, x: Lit, y: Lit) -> Lit {
+    let f = Lit::constant(false);
+    if x == f {
+        return y;
+    }
+    if x == !f {
+        return !y;
+    }
+    if y == f {
+        return x;
+    }
+    if y == !f {
+        return !x;
+    }
+    if x == y {
+        return f;
+    }
+    if x == !y {
+        return !f;
+    }
+    new_rel(dc, |l| LitVvec::cnf_xor(l, x, y))
+}
+
+pub fn cnf_ite(dc: &mut DagCnf, c: Lit, t: Lit, e: Lit) -> Lit {
+    let f = Lit::constant(false);
+    if c == !f || t == e {
+        return t;
+    }
+    if c == f {
+        return e;
+    }
+    if t == !f && e == f {
+        return c;
+    }
+    if t == f && e == !f {
+        return !c;
+    }
+    if t == c || t == !f {
+        return cnf_or(dc, c, e);
+    }
+    if e == c || e == f {
+        return cnf_and(dc, c, t);
+    }
+    if t == !c || t == f {
+        return cnf_and(dc, !c, e);
+    }
+    if e == !c || e == !f {
+        return cnf_or(dc, !c, t);
+    }
+    new_rel(dc, |l| LitVvec::cnf_ite(l, c, t, e))
+}
+
+/// Bitwise multiplexer selecting `t` if `c` holds and `e` otherwise.
+#[inline]
+pub fn cnf_mux(dc: &mut DagCnf, c: Lit, t: &[Lit], e: &[Lit]) -> LitVec {
+    t.iter()
+        .zip(e.iter())
+        .map(|(t, e)| cnf_ite(dc, c, *t, *e))
+        .collect()
+}
+
+/// Full adder encoded directly by the 8 parity clauses of the sum and the 6
+/// majority clauses of the carry.
+pub fn cnf_full_adder(dc: &mut DagCnf, x: Lit, y: Lit, c: Lit) -> (Lit, Lit) {
+    if is_const(x) || is_const(y) || is_const(c) {
+        let xy = cnf_xor(dc, x, y);
+        let r = cnf_xor(dc, xy, c);
+        let carry = cnf_ite(dc, xy, c, x);
+        return (r, carry);
+    }
+    let r = new_rel(dc, |r| {
+        let mut rel = LitVvec::new();
+        for m in 0..8_u32 {
+            let (a, b, d) = (m & 1 == 1, m & 2 == 2, m & 4 == 4);
+            rel.push(LitVec::from([
+                lit_if(x, !a),
+                lit_if(y, !b),
+                lit_if(c, !d),
+                lit_if(r, a ^ b ^ d),
+            ]));
+        }
+        rel
+    });
+    let carry = new_rel(dc, |o| {
+        let mut rel = LitVvec::new();
+        for (a, b) in [(x, y), (x, c), (y, c)] {
+            rel.push(LitVec::from([!a, !b, o]));
+            rel.push(LitVec::from([a, b, !o]));
+        }
+        rel
+    });
+    (r, carry)
+}
+
+pub fn cnf_adder(dc: &mut DagCnf, x: &[Lit], y: &[Lit], mut c: Lit) -> (LitVec, Lit) {
+    let mut r;
+    let mut res = LitVec::new();
+    for (x, y) in x.iter().zip(y.iter()) {
+        (r, c) = cnf_full_adder(dc, *x, *y, c);
+        res.push(r);
+    }
+    (res, c)
+}
+
+/// Returns `x - y` and whether `x >= y` as unsigned numbers.
+#[inline]
+pub fn cnf_sub(dc: &mut DagCnf, x: &[Lit], y: &[Lit]) -> (LitVec, Lit) {
+    let ny: LitVec = y.iter().map(|l| !*l).collect();
+    cnf_adder(dc, x, &ny, Lit::constant(true))
+}
+
+#[inline]
+pub fn cnf_neg(dc: &mut DagCnf, x: &[Lit]) -> LitVec {
+    let zero = vec![Lit::constant(false); x.len()];
+    cnf_sub(dc, &zero, x).0
+}
+
+#[inline]
+pub fn cnf_abs(dc: &mut DagCnf, x: &[Lit]) -> LitVec {
+    let nx = cnf_neg(dc, x);
+    cnf_mux(dc, x[x.len() - 1], &nx, x)
+}
+
+/// One comparator stage: `x < y` on the bits up to `x`, `y`, where `p` is
+/// the result on the lower bits.
+fn cnf_ult_step(dc: &mut DagCnf, x: Lit, y: Lit, p: Lit) -> Lit {
+    if is_const(x) || is_const(y) || is_const(p) {
+        let d = cnf_xor(dc, x, y);
+        return cnf_ite(dc, d, y, p);
+    }
+    new_rel(dc, |r| {
+        let mut rel = LitVvec::new();
+        rel.push(LitVec::from([x, !y, r]));
+        rel.push(LitVec::from([!x, y, !r]));
+        rel.push(LitVec::from([x, y, !p, r]));
+        rel.push(LitVec::from([x, y, p, !r]));
+        rel.push(LitVec::from([!x, !y, !p, r]));
+        rel.push(LitVec::from([!x, !y, p, !r]));
+        rel.push(LitVec::from([!y, !p, r]));
+        rel.push(LitVec::from([!x, p, !r]));
+        rel
+    })
+}
+
+pub fn cnf_ult(dc: &mut DagCnf, x: &[Lit], y: &[Lit]) -> Lit {
+    let mut res = Lit::constant(false);
+    for (x, y) in x.iter().zip(y.iter()) {
+        res = cnf_ult_step(dc, *x, *y, res);
+    }
+    res
+}
+
+pub fn cnf_slt(dc: &mut DagCnf, x: &[Lit], y: &[Lit]) -> Lit {
+    let len = x.len();
+    let res = cnf_ult(dc, &x[..len - 1], &y[..len - 1]);
+    cnf_ult_step(dc, y[len - 1], x[len - 1], res)
+}
+
+pub fn cnf_eq(dc: &mut DagCnf, x: &[Lit], y: &[Lit]) -> Lit {
+    let eqs: Vec<Lit> = x
+        .iter()
+        .zip(y.iter())
+        .map(|(x, y)| !cnf_xor(dc, *x, *y))
+        .collect();
+    cnf_and_n(dc, &eqs)
+}
+
+/// Barrel shifter, `fill` is shifted in and is also the result if the
+/// amount is not less than the width.
+pub fn cnf_shift(dc: &mut DagCnf, x: &[Lit], y: &[Lit], fill: Lit, left: bool) -> LitVec {
+    let width = x.len();
+    let mut res = LitVec::from(x);
+    let mut b = 0;
+    while b < y.len() && (1 << b) < width {
+        let step = 1 << b;
+        let prev = res.clone();
+        for j in 0..width {
+            let from = if left {
+                j.checked_sub(step).map_or(fill, |k| prev[k])
+            } else {
+                prev.get(j + step).copied().unwrap_or(fill)
+            };
+            res[j] = cnf_ite(dc, y[b], from, prev[j]);
+        }
+        b += 1;
+    }
+    let over = cnf_or_n(dc, &y[b..]);
+    let fills = vec![fill; width];
+    cnf_mux(dc, over, &fills, &res)
+}
+
+/// Barrel rotator over the amount `y` modulo the width of `x`.
+pub fn cnf_rotate(dc: &mut DagCnf, x: &[Lit], y: &[Lit], left: bool) -> LitVec {
+    let width = x.len();
+    let y = if width.is_power_of_two() {
+        LitVec::from(y)
+    } else {
+        // `y` is zero-extended to hold `width` before taking the remainder.
+        let len = y.len().max((usize::BITS - width.leading_zeros()) as usize);
+        let mut y = LitVec::from(y);
+        while y.len() < len {
+            y.push(Lit::constant(false));
+        }
+        let width_bv: LitVec = (0..len)
+            .map(|i| Lit::constant(i < usize::BITS as usize && (width >> i) & 1 == 1))
+            .collect();
+        cnf_udivrem(dc, &y, &width_bv).1
+    };
+    let mut res = LitVec::from(x);
+    let mut b = 0;
+    while b < y.len() && (1 << b) < width {
+        let step = (1 << b) % width;
+        let step = if left { width - step } else { step };
+        let prev = res.clone();
+        for j in 0..width {
+            res[j] = cnf_ite(dc, y[b], prev[(j + step) % width], prev[j]);
+        }
+        b += 1;
+    }
+    res
+}
+
+pub fn cnf_mul(dc: &mut DagCnf, x: &[Lit], y: &[Lit]) -> LitVec {
+    let len = x.len();
+    let mut res: LitVec = x.iter().map(|x| cnf_and(dc, *x, y[0])).collect();
+    for i in 1..len {
+        let mut c = Lit::constant(false);
+        for j in i..len {
+            let add = cnf_and(dc, y[i], x[j - i]);
+            (res[j], c) = cnf_full_adder(dc, res[j], add, c);
+        }
+    }
+    res
+}
+
+/// Restoring divider returning `(quotient, remainder)` with the SMT-LIB
+/// semantics of a zero divisor, each quotient bit is the carry of a trial
+/// subtraction.
+pub fn cnf_udivrem(dc: &mut DagCnf, x: &[Lit], y: &[Lit]) -> (LitVec, LitVec) {
+    let len = x.len();
+    let f = Lit::constant(false);
+    let mut ye = LitVec::from(y);
+    ye.push(f);
+    let mut q = LitVec::from(vec![f; len]);
+    let mut r = LitVec::from(vec![f; len]);
+    for i in (0..len).rev() {
+        let mut shifted = LitVec::from([x[i]]);
+        shifted.extend_from_slice(&r);
+        let (diff, ge) = cnf_sub(dc, &shifted, &ye);
+        q[i] = ge;
+        r = cnf_mux(dc, ge, &diff[..len], &shifted[..len]);
+    }
+    (q, r)
+}
+
+/// Multiplexer tree selecting element `index` of the flattened `array`.
+pub fn cnf_read(dc: &mut DagCnf, array: &[Lit], index: &[Lit]) -> LitVec {
+    let element_len = array.len() >> index.len();
+    let mut level: Vec<LitVec> = array.chunks(element_len).map(LitVec::from).collect();
+    for i in index.iter() {
+        level = level
+            .chunks(2)
+            .map(|p| cnf_mux(dc, *i, &p[1], &p[0]))
+            .collect();
+    }
+    level.pop().unwrap()
+}
+
+pub fn cnf_write(dc: &mut DagCnf, array: &[Lit], index: &[Lit], value: &[Lit]) -> LitVec {
+    let element_len = value.len();
+    let mut res = LitVec::new();
+    for (j, e) in array.chunks(element_len).enumerate() {
+        let hit: Vec<Lit> = index
+            .iter()
+            .enumerate()
+            .map(|(b, i)| lit_if(*i, (j >> b) & 1 == 1))
+            .collect();
+        let hit = cnf_and_n(dc, &hit);
+        res.extend_from_slice(&cnf_mux(dc, hit, value, e));
+    }
+    res
+}
@@ -1,23 +1,133 @@
-use super::define::define_core_op;
+use super::Op;
+use super::cnf::*;
+use super::define::{define_core_op, op_dyn_impl};
+use crate::bitblast::Polarity;
 use crate::{BvConst, Sort, Term, TermManager, TermResult, TermVec};
-use logic_form::{DagCnf, Lit, LitVvec};
+use logic_form::{DagCnf, Lit, LitVec, LitVvec};
 
 #[inline]
 fn bool_sort(_terms: &[Term]) -> Sort {
     Sort::Bv(1)
 }
 
-define_core_op!(Not, 1, bitblast: not_bitblast, cnf_encode: not_cnf_encode, simplify: not_simplify);
+#[inline]
+fn same_operand_polarity(polarity: Polarity, _i: usize) -> Polarity {
+    polarity
+}
+
+#[inline]
+fn bitwise_eval(terms: &[BvConst], f: impl Fn(bool, bool) -> bool) -> BvConst {
+    let c: Vec<bool> = terms[0]
+        .c
+        .iter()
+        .zip(terms[1].c.iter())
+        .map(|(x, y)| f(*x, *y))
+        .collect();
+    BvConst::new(&c)
+}
+
+#[inline]
+fn add_bools(x: &[bool], y: &[bool], mut c: bool) -> Vec<bool> {
+    let mut res = Vec::with_capacity(x.len());
+    for (&x, &y) in x.iter().zip(y.iter()) {
+        res.push(x ^ y ^ c);
+        c = (x & y) | (x & c) | (y & c);
+    }
+    res
+}
+
+#[inline]
+fn neg_bools(x: &[bool]) -> Vec<bool> {
+    let nx: Vec<bool> = x.iter().map(|b| !b).collect();
+    add_bools(&nx, &vec![false; x.len()], true)
+}
+
+#[inline]
+fn abs_bools(x: &[bool]) -> Vec<bool> {
+    if x[x.len() - 1] {
+        neg_bools(x)
+    } else {
+        x.to_vec()
+    }
+}
+
+#[inline]
+fn ult_bools(x: &[bool], y: &[bool]) -> bool {
+    for (x, y) in x.iter().rev().zip(y.iter().rev()) {
+        if x != y {
+            return *y;
+        }
+    }
+    false
+}
+
+#[inline]
+fn slt_bools(x: &[bool], y: &[bool]) -> bool {
+    let (xs, ys) = (x[x.len() - 1], y[y.len() - 1]);
+    if xs != ys { xs } else { ult_bools(x, y) }
+}
+
+/// Interprets `x` as an unsigned shift amount, saturating at `usize::MAX`.
+#[inline]
+fn shift_amount(x: &[bool]) -> usize {
+    x.iter()
+        .enumerate()
+        .filter(|(_, b)| **b)
+        .map(|(i, _)| 1_usize.checked_shl(i as u32).unwrap_or(usize::MAX))
+        .fold(0, usize::saturating_add)
+}
+
+fn mul_bools(x: &[bool], y: &[bool]) -> Vec<bool> {
+    let len = x.len();
+    let mut res = vec![false; len];
+    for i in (0..len).filter(|i| y[*i]) {
+        let mut shifted = vec![false; i];
+        shifted.extend_from_slice(&x[..len - i]);
+        res = add_bools(&res, &shifted, false);
+    }
+    res
+}
+
+/// Extends `x` to twice its width, by its sign bit if `signed`.
+#[inline]
+fn widen<T: Clone>(x: &[T], signed: bool, zero: T) -> Vec<T> {
+    let ext = if signed { x[x.len() - 1].clone() } else { zero };
+    let mut res = x.to_vec();
+    res.extend(std::iter::repeat_n(ext, x.len()));
+    res
+}
+
+fn udivrem_bools(x: &[bool], y: &[bool]) -> (Vec<bool>, Vec<bool>) {
+    let len = x.len();
+    let mut ye = y.to_vec();
+    ye.push(false);
+    let nye: Vec<bool> = ye.iter().map(|b| !b).collect();
+    let mut q = vec![false; len];
+    let mut r = vec![false; len];
+    for i in (0..len).rev() {
+        let mut shifted = vec![x[i]];
+        shifted.extend_from_slice(&r);
+        q[i] = !ult_bools(&shifted, &ye);
+        if q[i] {
+            shifted = add_bools(&shifted, &nye, true);
+        }
+        shifted.truncate(len);
+        r = shifted;
+    }
+    (q, r)
+}
+
+define_core_op!(Not, 1, bitblast: not_bitblast, cnf_encode: not_cnf_encode, simplify: not_simplify, eval: not_eval, bv_cnf_encode: not_bv_cnf_encode, operand_polarity: not_operand_polarity);
 fn not_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let x = &terms[0];
     if let Some(op) = x.try_op_term() {
         if op.op == Not {
-            dbg!("not1");
+            tm.record_rewrite("not1");
             return TermResult::Some(op[0].clone());
         }
     }
     if let Some(xc) = x.try_bv_const() {
-        dbg!("not2");
+        tm.record_rewrite("not2");
         return TermResult::Some(tm.bv_const(!xc));
     }
     TermResult::None
@@ -25,31 +135,40 @@ fn not_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
 fn not_bitblast(_tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     terms[0].iter().map(|t| !t).collect()
 }
+fn not_eval(terms: &[BvConst]) -> BvConst {
+    !&terms[0]
+}
 fn not_cnf_encode(_dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     !terms[0]
 }
+fn not_operand_polarity(polarity: Polarity, _i: usize) -> Polarity {
+    !polarity
+}
+fn not_bv_cnf_encode(_dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    terms[0].iter().map(|l| !*l).collect()
+}
 
-define_core_op!(And, 2, bitblast: and_bitblast, cnf_encode: and_cnf_encode, simplify: and_simplify);
-fn and_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
+define_core_op!(And, 2, bitblast: and_bitblast, cnf_encode: and_cnf_encode, simplify: and_simplify, eval: and_eval, bv_cnf_encode: and_bv_cnf_encode, cnf_encode_polarity: and_cnf_encode_polarity, operand_polarity: same_operand_polarity);
+fn and_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
     let simp = |a: &Term, b: &Term| {
         if let Some(ac) = a.try_bv_const() {
             if ac.is_ones() {
-                dbg!("and1");
+                tm.record_rewrite("and1");
                 return TermResult::Some(b.clone());
             }
             if ac.is_zero() {
-                dbg!("and2");
+                tm.record_rewrite("and2");
                 return TermResult::Some(a.clone());
             }
         }
         if a == b {
-            dbg!("and3");
+            tm.record_rewrite("and3");
             return TermResult::Some(a.clone());
         }
         if a == &!b {
-            dbg!("and4");
+            tm.record_rewrite("and4");
             return TermResult::Some(a.mk_bv_const_zero());
         }
         if let Some(aop) = a.try_op_term() {
@@ -57,28 +176,28 @@ fn and_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
                 if let Some(bop) = b.try_op_term() {
                     if bop.op == And {
                         if aop[0] == bop[0] {
-                            dbg!("and5");
+                            tm.record_rewrite("and5");
                             return TermResult::Some(&aop[0] & &aop[1] & &bop[1]);
                         }
                         if aop[0] == bop[1] {
-                            dbg!("and5");
+                            tm.record_rewrite("and5");
                             return TermResult::Some(&aop[0] & &aop[1] & &bop[0]);
                         }
                     }
                 }
                 if b == &aop[0] {
-                    dbg!("and6");
+                    tm.record_rewrite("and6");
                     return TermResult::Some(b & &aop[1]);
                 }
                 if b == &aop[1] {
-                    dbg!("and6");
+                    tm.record_rewrite("and6");
                     return TermResult::Some(b & &aop[0]);
                 }
             }
             if aop.op == Not {
                 if let Some(bop) = b.try_op_term() {
                     if bop.op == Not {
-                        dbg!("and7");
+                        tm.record_rewrite("and7");
                         return TermResult::Some(!(&aop[0] | &bop[0]));
                     }
                 }
@@ -87,11 +206,11 @@ fn and_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
                 if let Some(bop) = b.try_op_term() {
                     if bop.op == Or {
                         if aop[0] == bop[0] {
-                            dbg!("and8");
+                            tm.record_rewrite("and8");
                             return TermResult::Some(&aop[0] | (&aop[1] & &bop[1]));
                         }
                         if aop[0] == bop[1] {
-                            dbg!("and8");
+                            tm.record_rewrite("and8");
                             return TermResult::Some(&aop[0] | (&aop[1] & &bop[0]));
                         }
                     }
@@ -106,57 +225,85 @@ fn and_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
 fn and_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     tm.new_op_terms_elementwise(And, &terms[0], &terms[1])
 }
+fn and_eval(terms: &[BvConst]) -> BvConst {
+    bitwise_eval(terms, |x, y| x & y)
+}
 fn and_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     let l = dc.new_var().lit();
     dc.add_rel(l.var(), &LitVvec::cnf_and(l, terms));
     l
 }
+fn and_cnf_encode_polarity(dc: &mut DagCnf, terms: &[Lit], polarity: Polarity) -> Lit {
+    let l = dc.new_var().lit();
+    let mut rel = LitVvec::new();
+    if polarity.positive() {
+        rel.extend(terms.iter().map(|x| LitVec::from([!l, *x])));
+    }
+    if polarity.negative() {
+        let mut c = LitVec::from([l]);
+        c.extend(terms.iter().map(|x| !*x));
+        rel.push(c);
+    }
+    dc.add_rel(l.var(), &rel);
+    l
+}
+fn and_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    x.iter()
+        .zip(y.iter())
+        .map(|(x, y)| cnf_and(dc, *x, *y))
+        .collect()
+}
 
-define_core_op!(Or, 2, bitblast: or_bitblast, cnf_encode: or_cnf_encode, simplify: or_simplify);
-fn or_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
+define_core_op!(Or, 2, bitblast: or_bitblast, cnf_encode: or_cnf_encode, simplify: or_simplify, eval: or_eval, bv_cnf_encode: or_bv_cnf_encode, cnf_encode_polarity: or_cnf_encode_polarity, operand_polarity: same_operand_polarity);
+fn or_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
     let simp = |a: &Term, b: &Term| {
         if let Some(ac) = a.try_bv_const() {
             if ac.is_ones() {
+                tm.record_rewrite("or1");
                 return TermResult::Some(a.clone());
             }
             if ac.is_zero() {
+                tm.record_rewrite("or2");
                 return TermResult::Some(b.clone());
             }
         }
         if a == b {
+            tm.record_rewrite("or3");
             return TermResult::Some(a.clone());
         }
         if a == &!b {
+            tm.record_rewrite("or4");
             return TermResult::Some(a.mk_bv_const_ones());
         }
         if let Some(aop) = a.try_op_term() {
             if aop.op == Or {
                 if b == &aop[0] {
-                    dbg!("or5");
+                    tm.record_rewrite("or5");
                     return TermResult::Some(b | &aop[1]);
                 }
                 if b == &aop[1] {
-                    dbg!("or6");
+                    tm.record_rewrite("or6");
                     return TermResult::Some(b | &aop[0]);
                 }
             }
             if aop.op == Not {
                 if let Some(bop) = b.try_op_term() {
                     if bop.op == Not {
-                        dbg!("or7");
+                        tm.record_rewrite("or7");
                         return TermResult::Some(!(&aop[0] & &bop[0]));
                     }
                 }
             }
             if aop.op == Ite {
                 if b == &aop[0] {
-                    dbg!("or8");
+                    tm.record_rewrite("or8");
                     return TermResult::Some(b | &aop[2]);
                 }
                 if b == &!&aop[0] {
-                    dbg!("or8");
+                    tm.record_rewrite("or8");
                     return TermResult::Some(b | &aop[1]);
                 }
             }
@@ -164,11 +311,11 @@ fn or_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
                 if let Some(bop) = b.try_op_term() {
                     if bop.op == And {
                         if aop[0] == bop[0] {
-                            dbg!("or9");
+                            tm.record_rewrite("or9");
                             return TermResult::Some(&aop[0] & (&aop[1] | &bop[1]));
                         }
                         if aop[0] == bop[1] {
-                            dbg!("or9");
+                            tm.record_rewrite("or9");
                             return TermResult::Some(&aop[0] & (&aop[1] | &bop[0]));
                         }
                     }
@@ -183,66 +330,120 @@ fn or_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
 fn or_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     tm.new_op_terms_elementwise(Or, &terms[0], &terms[1])
 }
+fn or_eval(terms: &[BvConst]) -> BvConst {
+    bitwise_eval(terms, |x, y| x | y)
+}
 fn or_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     let l = dc.new_var().lit();
     dc.add_rel(l.var(), &LitVvec::cnf_or(l, terms));
     l
 }
+fn or_cnf_encode_polarity(dc: &mut DagCnf, terms: &[Lit], polarity: Polarity) -> Lit {
+    let l = dc.new_var().lit();
+    let mut rel = LitVvec::new();
+    if polarity.positive() {
+        let mut c = LitVec::from([!l]);
+        c.extend_from_slice(terms);
+        rel.push(c);
+    }
+    if polarity.negative() {
+        rel.extend(terms.iter().map(|x| LitVec::from([l, !*x])));
+    }
+    dc.add_rel(l.var(), &rel);
+    l
+}
+fn or_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    x.iter()
+        .zip(y.iter())
+        .map(|(x, y)| cnf_or(dc, *x, *y))
+        .collect()
+}
 
-define_core_op!(Xor, 2, bitblast: xor_bitblast, cnf_encode: xor_cnf_encode, simplify: xor_simplify);
-fn xor_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
+define_core_op!(Xor, 2, bitblast: xor_bitblast, cnf_encode: xor_cnf_encode, simplify: xor_simplify, eval: xor_eval, bv_cnf_encode: xor_bv_cnf_encode, cnf_encode_polarity: xor_cnf_encode_polarity);
+/// The rewrite of an `Xor` with the name of its rule, shared by `Eq`.
+fn xor_rewrite(terms: &[Term]) -> Option<(&'static str, Term)> {
     let x = &terms[0];
     let y = &terms[1];
     let simp = |a: &Term, b: &Term| {
         if let Some(ac) = a.try_bv_const() {
             if ac.is_ones() {
-                dbg!("xor1");
-                return TermResult::Some(!b.clone());
+                return Some(("xor1", !b.clone()));
             }
             if ac.is_zero() {
-                dbg!("xor2");
-                return TermResult::Some(b.clone());
+                return Some(("xor2", b.clone()));
             }
         }
         if a == b {
-            dbg!("xor3");
-            return TermResult::Some(a.mk_bv_const_zero());
+            return Some(("xor3", a.mk_bv_const_zero()));
         }
         if a == &!b {
-            dbg!("xor4");
-            return TermResult::Some(a.mk_bv_const_ones());
+            return Some(("xor4", a.mk_bv_const_ones()));
         }
-        TermResult::None
+        None
     };
-    simp(x, y)?;
-    simp(y, x)
+    simp(x, y).or_else(|| simp(y, x))
+}
+fn xor_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    match xor_rewrite(terms) {
+        Some((rule, res)) => {
+            tm.record_rewrite(rule);
+            TermResult::Some(res)
+        }
+        None => TermResult::None,
+    }
 }
 fn xor_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     tm.new_op_terms_elementwise(Xor, &terms[0], &terms[1])
 }
+fn xor_eval(terms: &[BvConst]) -> BvConst {
+    bitwise_eval(terms, |x, y| x ^ y)
+}
 fn xor_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     let l = dc.new_var().lit();
     dc.add_rel(l.var(), &LitVvec::cnf_xor(l, terms[0], terms[1]));
     l
 }
+fn xor_cnf_encode_polarity(dc: &mut DagCnf, terms: &[Lit], polarity: Polarity) -> Lit {
+    let (x, y) = (terms[0], terms[1]);
+    let l = dc.new_var().lit();
+    let mut rel = LitVvec::new();
+    if polarity.positive() {
+        rel.push(LitVec::from([!l, x, y]));
+        rel.push(LitVec::from([!l, !x, !y]));
+    }
+    if polarity.negative() {
+        rel.push(LitVec::from([l, !x, y]));
+        rel.push(LitVec::from([l, x, !y]));
+    }
+    dc.add_rel(l.var(), &rel);
+    l
+}
+fn xor_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    x.iter()
+        .zip(y.iter())
+        .map(|(x, y)| cnf_xor(dc, *x, *y))
+        .collect()
+}
 
-define_core_op!(Eq, 2, sort: bool_sort, bitblast: eq_bitblast, cnf_encode: eq_cnf_encode, simplify: eq_simplify);
+define_core_op!(Eq, 2, sort: bool_sort, bitblast: eq_bitblast, cnf_encode: eq_cnf_encode, simplify: eq_simplify, eval: eq_eval, bv_cnf_encode: eq_bv_cnf_encode, cnf_encode_polarity: eq_cnf_encode_polarity);
 fn eq_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
     let mut simp = |a: &Term, b: &Term| {
         if a.is_bool() {
-            if let TermResult::Some(s) = xor_simplify(tm, terms) {
-                dbg!("eq1");
+            if let Some((_, s)) = xor_rewrite(terms) {
+                tm.record_rewrite("eq1");
                 return TermResult::Some(!s);
             }
         }
         if a == b {
-            dbg!("eq2");
+            tm.record_rewrite("eq2");
             return TermResult::Some(tm.bool_const(true));
         }
         if a == &!b {
-            dbg!("eq3");
+            tm.record_rewrite("eq3");
             return TermResult::Some(tm.bool_const(false));
         }
         TermResult::None
@@ -254,33 +455,42 @@ fn eq_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let neqs = tm.new_op_terms_elementwise(Eq, &terms[0], &terms[1]);
     TermVec::from([tm.new_op_terms_fold(And, &neqs)])
 }
+fn eq_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&[terms[0] == terms[1]])
+}
 fn eq_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     let l = dc.new_var().lit();
     dc.add_rel(l.var(), &LitVvec::cnf_xnor(l, terms[0], terms[1]));
     l
 }
+fn eq_cnf_encode_polarity(dc: &mut DagCnf, terms: &[Lit], polarity: Polarity) -> Lit {
+    !xor_cnf_encode_polarity(dc, terms, !polarity)
+}
+fn eq_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    LitVec::from([cnf_eq(dc, &terms[0], &terms[1])])
+}
 
-define_core_op!(Ult, 2, sort: bool_sort, bitblast: ult_bitblast, simplify: ult_simplify);
+define_core_op!(Ult, 2, sort: bool_sort, bitblast: ult_bitblast, simplify: ult_simplify, eval: ult_eval, bv_cnf_encode: ult_bv_cnf_encode);
 fn ult_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
     if let Some(xc) = x.try_bv_const() {
         if xc.is_zero() {
-            dbg!("ult0");
+            tm.record_rewrite("ult0");
             return TermResult::Some(!x.op1(Eq, y));
         }
         if xc.is_ones() {
-            dbg!("ult1");
+            tm.record_rewrite("ult1");
             return TermResult::Some(tm.bool_const(false));
         }
     }
     if let Some(yc) = y.try_bv_const() {
         if yc.is_zero() {
-            dbg!("ult2");
+            tm.record_rewrite("ult2");
             return TermResult::Some(tm.bool_const(false));
         }
         if yc.is_ones() {
-            dbg!("ult3");
+            tm.record_rewrite("ult3");
             return TermResult::Some(!x.op1(Eq, y));
         }
     }
@@ -293,8 +503,14 @@ fn ult_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     TermVec::from([res])
 }
+fn ult_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&[ult_bools(&terms[0].c, &terms[1].c)])
+}
+fn ult_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    LitVec::from([cnf_ult(dc, &terms[0], &terms[1])])
+}
 
-define_core_op!(Slt, 2, sort: bool_sort, bitblast: slt_bitblast);
+define_core_op!(Slt, 2, sort: bool_sort, bitblast: slt_bitblast, eval: slt_eval, bv_cnf_encode: slt_bv_cnf_encode);
 fn slt_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let x = &terms[0];
     let y = &terms[1];
@@ -309,6 +525,12 @@ fn slt_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     TermVec::from([ls | (eqs & el)])
 }
+fn slt_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&[slt_bools(&terms[0].c, &terms[1].c)])
+}
+fn slt_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    LitVec::from([cnf_slt(dc, &terms[0], &terms[1])])
+}
 
 fn get_shift_size(x: usize) -> usize {
     let mut pow2 = 1;
@@ -320,7 +542,7 @@ fn get_shift_size(x: usize) -> usize {
     res
 }
 
-define_core_op!(Sll, 2, bitblast: sll_bitblast);
+define_core_op!(Sll, 2, bitblast: sll_bitblast, eval: sll_eval, bv_cnf_encode: sll_bv_cnf_encode);
 fn sll_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert!(x.len() == y.len());
@@ -333,12 +555,12 @@ fn sll_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     for shift_bit in 0..shift_size {
         let shift_step = 1 << shift_bit;
         let shift = &y[shift_bit];
+        for j in (shift_step..width).rev() {
+            res[j] = tm.new_op_term(Ite, [shift, &res[j - shift_step], &res[j]]);
+        }
         for j in 0..shift_step {
             res[j] = &!shift & &res[j];
         }
-        for j in shift_step..width {
-            res[j] = tm.new_op_term(Ite, [shift, &res[j - shift_step], &res[j]]);
-        }
     }
     let width_bv = tm
         .bv_const_from_usize(width, width)
@@ -353,8 +575,17 @@ fn sll_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn sll_eval(terms: &[BvConst]) -> BvConst {
+    let x = &terms[0].c;
+    let s = shift_amount(&terms[1].c);
+    let c: Vec<bool> = (0..x.len()).map(|i| i >= s && x[i - s]).collect();
+    BvConst::new(&c)
+}
+fn sll_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_shift(dc, &terms[0], &terms[1], Lit::constant(false), true)
+}
 
-define_core_op!(Srl, 2, bitblast: srl_bitblast);
+define_core_op!(Srl, 2, bitblast: srl_bitblast, eval: srl_eval, bv_cnf_encode: srl_bv_cnf_encode);
 fn srl_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert!(x.len() == y.len());
@@ -387,8 +618,19 @@ fn srl_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn srl_eval(terms: &[BvConst]) -> BvConst {
+    let x = &terms[0].c;
+    let s = shift_amount(&terms[1].c);
+    let c: Vec<bool> = (0..x.len())
+        .map(|i| i.checked_add(s).is_some_and(|j| j < x.len() && x[j]))
+        .collect();
+    BvConst::new(&c)
+}
+fn srl_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_shift(dc, &terms[0], &terms[1], Lit::constant(false), false)
+}
 
-define_core_op!(Sra, 2, bitblast: sra_bitblast);
+define_core_op!(Sra, 2, bitblast: sra_bitblast, eval: sra_eval, bv_cnf_encode: sra_bv_cnf_encode);
 fn sra_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert!(x.len() == y.len());
@@ -421,15 +663,132 @@ fn sra_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn sra_eval(terms: &[BvConst]) -> BvConst {
+    let x = &terms[0].c;
+    let s = shift_amount(&terms[1].c);
+    let sign = x[x.len() - 1];
+    let c: Vec<bool> = (0..x.len())
+        .map(|i| match i.checked_add(s) {
+            Some(j) if j < x.len() => x[j],
+            _ => sign,
+        })
+        .collect();
+    BvConst::new(&c)
+}
+fn sra_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let x = &terms[0];
+    cnf_shift(dc, x, &terms[1], x[x.len() - 1], false)
+}
+
+/// Interprets `x` as an unsigned rotation amount modulo `width`.
+#[inline]
+fn rotate_amount(x: &[bool], width: usize) -> usize {
+    x.iter()
+        .rev()
+        .fold(0, |acc, b| (acc * 2 + *b as usize) % width)
+}
+
+/// Barrel rotator over the amount `y` modulo the width of `x`.
+fn rotate_bits(tm: &mut TermManager, x: &[Term], y: &[Term], left: bool) -> TermVec {
+    let width = x.len();
+    let y = if width.is_power_of_two() {
+        TermVec::from(y)
+    } else {
+        // `y` is zero-extended to hold `width` before taking the remainder.
+        let len = y.len().max((usize::BITS - width.leading_zeros()) as usize);
+        let mut y = TermVec::from(y);
+        let f = tm.bool_const(false);
+        while y.len() < len {
+            y.push(f.clone());
+        }
+        let width_bv = tm
+            .bv_const_from_usize(width, len)
+            .try_bv_const()
+            .unwrap()
+            .clone();
+        let width_bv = width_bv.bitblast(tm);
+        udivrem_bits(tm, &y, &width_bv).1
+    };
+    let mut res = TermVec::from(x);
+    for (b, shift) in y.iter().enumerate().take(get_shift_size(width)) {
+        let step = (1 << b) % width;
+        let step = if left { width - step } else { step };
+        res = (0..width)
+            .map(|j| shift.ite(&res[(j + step) % width], &res[j]))
+            .collect();
+    }
+    res
+}
 
-define_core_op!(Ite, 3, sort: ite_sort, bitblast: ite_bitblast, cnf_encode: ite_cnf_encode, simplify: ite_simplify);
+/// Rewrites a rotation by a constant amount into a `Concat` of two slices.
+fn const_rotate(terms: &[Term], left: bool) -> Option<Term> {
+    let (x, y) = (&terms[0], &terms[1]);
+    let yc = y.try_bv_const()?;
+    let width = x.bv_len();
+    let mut k = rotate_amount(&yc.c, width);
+    if !left {
+        k = (width - k) % width;
+    }
+    if k == 0 {
+        return Some(x.clone());
+    }
+    let low = x.slice(width - k - 1, 0);
+    let high = x.slice(width - 1, width - k);
+    Some(low.op1(Concat, &high))
+}
+
+define_core_op!(Rol, 2, bitblast: rol_bitblast, simplify: rol_simplify, eval: rol_eval, bv_cnf_encode: rol_bv_cnf_encode);
+fn rol_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    if let Some(r) = const_rotate(terms, true) {
+        tm.record_rewrite("rol1");
+        return TermResult::Some(r);
+    }
+    TermResult::None
+}
+fn rol_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    rotate_bits(tm, &terms[0], &terms[1], true)
+}
+fn rol_eval(terms: &[BvConst]) -> BvConst {
+    let x = &terms[0].c;
+    let width = x.len();
+    let s = rotate_amount(&terms[1].c, width);
+    let c: Vec<bool> = (0..width).map(|i| x[(i + width - s) % width]).collect();
+    BvConst::new(&c)
+}
+fn rol_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_rotate(dc, &terms[0], &terms[1], true)
+}
+
+define_core_op!(Ror, 2, bitblast: ror_bitblast, simplify: ror_simplify, eval: ror_eval, bv_cnf_encode: ror_bv_cnf_encode);
+fn ror_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    if let Some(r) = const_rotate(terms, false) {
+        tm.record_rewrite("ror1");
+        return TermResult::Some(r);
+    }
+    TermResult::None
+}
+fn ror_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    rotate_bits(tm, &terms[0], &terms[1], false)
+}
+fn ror_eval(terms: &[BvConst]) -> BvConst {
+    let x = &terms[0].c;
+    let width = x.len();
+    let s = rotate_amount(&terms[1].c, width);
+    let c: Vec<bool> = (0..width).map(|i| x[(i + s) % width]).collect();
+    BvConst::new(&c)
+}
+fn ror_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_rotate(dc, &terms[0], &terms[1], false)
+}
+
+define_core_op!(Ite, 3, sort: ite_sort, bitblast: ite_bitblast, cnf_encode: ite_cnf_encode, simplify: ite_simplify, eval: ite_eval, bv_cnf_encode: ite_bv_cnf_encode, cnf_encode_polarity: ite_cnf_encode_polarity, operand_polarity: ite_operand_polarity);
 fn ite_sort(terms: &[Term]) -> Sort {
     terms[1].sort()
 }
-fn ite_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
+fn ite_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let (c, t, e) = (&terms[0], &terms[1], &terms[2]);
     if let Some(cc) = c.try_bv_const() {
-        dbg!("ite1");
+        tm.record_rewrite("ite1");
         if cc.is_ones() {
             return TermResult::Some(t.clone());
         } else {
@@ -437,33 +796,33 @@ fn ite_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
         }
     }
     if t == e {
-        dbg!("ite2");
+        tm.record_rewrite("ite2");
         return TermResult::Some(t.clone());
     }
     if let Some(cop) = c.try_op_term() {
         if cop.op == Not {
-            dbg!("ite2.5");
+            tm.record_rewrite("ite2.5");
             return TermResult::Some(cop[0].ite(e, t));
         }
     }
     if t.is_bool() {
         if let Some(ec) = e.try_bv_const() {
             if ec.is_zero() {
-                dbg!("ite3");
+                tm.record_rewrite("ite3");
                 return TermResult::Some(c & t);
             }
             if ec.is_ones() {
-                dbg!("ite4");
+                tm.record_rewrite("ite4");
                 return TermResult::Some(!c | t);
             }
         }
         if let Some(tc) = t.try_bv_const() {
             if tc.is_zero() {
-                dbg!("ite5");
+                tm.record_rewrite("ite5");
                 return TermResult::Some(!c & e);
             }
             if tc.is_ones() {
-                dbg!("ite6");
+                tm.record_rewrite("ite6");
                 return TermResult::Some(c | e);
             }
         }
@@ -471,7 +830,7 @@ fn ite_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
     // let tr = t.replace(c, &tm.bool_const(true));
     // let er = e.replace(c, &tm.bool_const(false));
     // if tr != t || er != e {
-    //     dbg!("ite7");
+    //     tm.record_rewrite("ite7");
     //     return TermResult::Some(c.ite(&tr, &er));
     // }
     TermResult::None
@@ -483,19 +842,48 @@ fn ite_bitblast(_tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn ite_eval(terms: &[BvConst]) -> BvConst {
+    if terms[0].c[0] {
+        terms[1].clone()
+    } else {
+        terms[2].clone()
+    }
+}
 fn ite_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     let l = dc.new_var().lit();
     dc.add_rel(l.var(), &LitVvec::cnf_ite(l, terms[0], terms[1], terms[2]));
     l
 }
+fn ite_cnf_encode_polarity(dc: &mut DagCnf, terms: &[Lit], polarity: Polarity) -> Lit {
+    let (c, t, e) = (terms[0], terms[1], terms[2]);
+    let l = dc.new_var().lit();
+    let mut rel = LitVvec::new();
+    if polarity.positive() {
+        rel.push(LitVec::from([!l, !c, t]));
+        rel.push(LitVec::from([!l, c, e]));
+    }
+    if polarity.negative() {
+        rel.push(LitVec::from([l, !c, !t]));
+        rel.push(LitVec::from([l, c, !e]));
+    }
+    dc.add_rel(l.var(), &rel);
+    l
+}
+fn ite_operand_polarity(polarity: Polarity, i: usize) -> Polarity {
+    if i == 0 { Polarity::Both } else { polarity }
+}
+fn ite_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_mux(dc, terms[0][0], &terms[1], &terms[2])
+}
 
-define_core_op!(Concat, 2, sort: concat_sort, bitblast: concat_bitblast, simplify: concat_simplify);
+define_core_op!(Concat, 2, sort: concat_sort, bitblast: concat_bitblast, simplify: concat_simplify, eval: concat_eval, bv_cnf_encode: concat_bv_cnf_encode);
 fn concat_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
     if let (Some(xc), Some(yc)) = (x.try_bv_const(), y.try_bv_const()) {
         let mut c = yc.c.clone();
         c.extend_from_slice(&xc.c);
+        tm.record_rewrite("concat1");
         return TermResult::Some(tm.bv_const(BvConst::new(&c)));
     }
     TermResult::None
@@ -508,41 +896,179 @@ fn concat_bitblast(_tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     res.extend_from_slice(&terms[0]);
     res
 }
-
-define_core_op!(Sext, 2, sort: sext_sort, bitblast: sext_bitblast);
-fn sext_sort(terms: &[Term]) -> Sort {
-    Sort::Bv(terms[0].bv_len() + terms[1].bv_len())
+fn concat_eval(terms: &[BvConst]) -> BvConst {
+    let mut c = terms[1].c.clone();
+    c.extend_from_slice(&terms[0].c);
+    BvConst::new(&c)
 }
-fn sext_bitblast(_tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
-    let x = &terms[0];
-    let mut res = x.clone();
-    let ext = vec![x[x.len() - 1].clone(); terms[1].len()];
-    res.extend(ext);
+fn concat_bv_cnf_encode(_dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let mut res = terms[1].clone();
+    res.extend_from_slice(&terms[0]);
     res
 }
 
-define_core_op!(Slice, 3, sort: slice_sort, bitblast: slice_bitblast, simplify: slice_simplify);
-fn slice_simplify(_tm: &mut TermManager, terms: &[Term]) -> TermResult {
-    if let Some(op) = terms[0].try_op_term() {
-        if op.op == Concat {
-            panic!()
+/// Sign extension by the given number of bits.
+#[derive(Hash, Debug, PartialEq, Clone, Copy)]
+pub struct Sext(pub usize);
+
+inventory::submit! {crate::op::ParamOpCollect("sext", |p| match p {
+    [ext] => Some(Sext(*ext).into()),
+    _ => None,
+})}
+
+impl Op for Sext {
+    op_dyn_impl!();
+
+    #[inline]
+    fn num_operand(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn is_core(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn sort(&self, terms: &[Term]) -> Sort {
+        Sort::Bv(terms[0].bv_len() + self.0)
+    }
+
+    fn eval(&self, terms: &[BvConst]) -> BvConst {
+        let x = &terms[0].c;
+        let mut c = x.clone();
+        c.extend(std::iter::repeat_n(x[x.len() - 1], self.0));
+        BvConst::new(&c)
+    }
+
+    fn bitblast(&self, _tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+        let x = &terms[0];
+        let mut res = x.clone();
+        res.extend(std::iter::repeat_n(x[x.len() - 1].clone(), self.0));
+        res
+    }
+
+    fn bv_cnf_encode(&self, _dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+        let x = &terms[0];
+        let mut res = x.clone();
+        res.extend(std::iter::repeat_n(x[x.len() - 1], self.0));
+        res
+    }
+}
+
+/// Pushes the slice `hi..=lo` of `x` through concats, slices and sign
+/// extensions.
+fn slice_rewrite(tm: &mut TermManager, x: &Term, hi: usize, lo: usize) -> Option<Term> {
+    if lo == 0 && hi + 1 == x.bv_len() {
+        tm.record_rewrite("slice1");
+        return Some(x.clone());
+    }
+    let op = x.try_op_term()?;
+    if op.op == Concat {
+        tm.record_rewrite("slice2");
+        let (a, b) = (&op[0], &op[1]);
+        let bl = b.bv_len();
+        return Some(if hi < bl {
+            slice_term(tm, b, hi, lo)
+        } else if lo >= bl {
+            slice_term(tm, a, hi - bl, lo - bl)
+        } else {
+            let h = slice_term(tm, a, hi - bl, 0);
+            let l = slice_term(tm, b, bl - 1, lo);
+            tm.new_op_term(Concat, [&h, &l])
+        });
+    }
+    if let Some(s) = op.op.downcast_ref::<Slice>() {
+        tm.record_rewrite("slice3");
+        return Some(slice_term(tm, &op[0], hi + s.lo, lo + s.lo));
+    }
+    if op.op.downcast_ref::<Sext>().is_some() {
+        tm.record_rewrite("slice4");
+        let y = &op[0];
+        let yl = y.bv_len();
+        if hi < yl {
+            return Some(slice_term(tm, y, hi, lo));
         }
+        let l = lo.min(yl - 1);
+        let s = slice_term(tm, y, yl - 1, l);
+        let ext = hi + 1 - lo - (yl - l);
+        if ext == 0 {
+            return Some(s);
+        }
+        return Some(tm.new_op_term(Sext(ext), [&s]));
     }
-    TermResult::None
+    None
 }
-fn slice_sort(terms: &[Term]) -> Sort {
-    Sort::Bv(terms[1].bv_len() - terms[2].bv_len() + 1)
+
+#[inline]
+fn slice_term(tm: &mut TermManager, x: &Term, hi: usize, lo: usize) -> Term {
+    slice_rewrite(tm, x, hi, lo).unwrap_or_else(|| tm.new_op_term(Slice { hi, lo }, [x]))
 }
-fn slice_bitblast(_tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
-    let l = terms[2].len();
-    let h = terms[1].len();
-    terms[0][l..=h].iter().cloned().collect()
+
+/// Bits `hi` down to `lo` of a bit-vector, both inclusive.
+#[derive(Hash, Debug, PartialEq, Clone, Copy)]
+pub struct Slice {
+    pub hi: usize,
+    pub lo: usize,
 }
 
-define_core_op!(Redxor, 1, sort: bool_sort, bitblast: redxor_bitblast);
+inventory::submit! {crate::op::ParamOpCollect("slice", |p| match p {
+    [hi, lo] if lo <= hi => Some(Slice { hi: *hi, lo: *lo }.into()),
+    _ => None,
+})}
+
+impl Op for Slice {
+    op_dyn_impl!();
+
+    #[inline]
+    fn num_operand(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn is_core(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn sort(&self, terms: &[Term]) -> Sort {
+        debug_assert!(self.lo <= self.hi && self.hi < terms[0].bv_len());
+        Sort::Bv(self.hi - self.lo + 1)
+    }
+
+    fn simplify(&self, tm: &mut TermManager, terms: &[Term]) -> TermResult {
+        match slice_rewrite(tm, &terms[0], self.hi, self.lo) {
+            Some(res) => TermResult::Some(res),
+            None => TermResult::None,
+        }
+    }
+
+    fn eval(&self, terms: &[BvConst]) -> BvConst {
+        BvConst::new(&terms[0].c[self.lo..=self.hi])
+    }
+
+    fn bitblast(&self, _tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+        terms[0][self.lo..=self.hi].iter().cloned().collect()
+    }
+
+    fn bv_cnf_encode(&self, _dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+        LitVec::from(&terms[0][self.lo..=self.hi])
+    }
+}
+
+define_core_op!(Redxor, 1, sort: bool_sort, bitblast: redxor_bitblast, eval: redxor_eval, bv_cnf_encode: redxor_bv_cnf_encode);
 fn redxor_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     TermVec::from([tm.new_op_terms_fold(Xor, terms[0].iter())])
 }
+fn redxor_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&[terms[0].c.iter().fold(false, |acc, x| acc ^ x)])
+}
+fn redxor_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let res = terms[0]
+        .iter()
+        .fold(Lit::constant(false), |acc, x| cnf_xor(dc, acc, *x));
+    LitVec::from([res])
+}
 
 #[inline]
 fn full_adder(tm: &mut TermManager, x: &Term, y: &Term, c: &Term) -> (Term, Term) {
@@ -554,19 +1080,53 @@ fn full_adder(tm: &mut TermManager, x: &Term, y: &Term, c: &Term) -> (Term, Term
     (r, c)
 }
 
-define_core_op!(Add, 2, bitblast: add_bitblast);
-fn add_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+#[inline]
+fn ripple_carry_adder(
+    tm: &mut TermManager,
+    x: &[Term],
+    y: &[Term],
+    mut c: Term,
+) -> (TermVec, Term) {
     let mut r;
-    let mut c = tm.bool_const(false);
     let mut res = TermVec::new();
-    for (x, y) in terms[0].iter().zip(terms[1].iter()) {
+    for (x, y) in x.iter().zip(y.iter()) {
         (r, c) = full_adder(tm, x, y, &c);
         res.push(r);
     }
-    res
+    (res, c)
+}
+
+#[inline]
+fn sub_bits(tm: &mut TermManager, x: &[Term], y: &[Term]) -> TermVec {
+    let ny: TermVec = y.iter().map(|t| !t).collect();
+    let c = tm.bool_const(true);
+    ripple_carry_adder(tm, x, &ny, c).0
 }
 
-define_core_op!(Mul, 2, bitblast: mul_bitblast);
+#[inline]
+fn neg_bits(tm: &mut TermManager, x: &[Term]) -> TermVec {
+    let zero = vec![tm.bool_const(false); x.len()];
+    sub_bits(tm, &zero, x)
+}
+
+#[inline]
+fn ite_bits(c: &Term, t: &[Term], e: &[Term]) -> TermVec {
+    t.iter().zip(e.iter()).map(|(t, e)| c.ite(t, e)).collect()
+}
+
+define_core_op!(Add, 2, bitblast: add_bitblast, eval: add_eval, bv_cnf_encode: add_bv_cnf_encode);
+fn add_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let c = tm.bool_const(false);
+    ripple_carry_adder(tm, &terms[0], &terms[1], c).0
+}
+fn add_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&add_bools(&terms[0].c, &terms[1].c, false))
+}
+fn add_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_adder(dc, &terms[0], &terms[1], Lit::constant(false)).0
+}
+
+define_core_op!(Mul, 2, bitblast: mul_bitblast, eval: mul_eval, bv_cnf_encode: mul_bv_cnf_encode);
 fn mul_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let x = &terms[0];
     let y = &terms[1];
@@ -582,8 +1142,389 @@ fn mul_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn mul_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&mul_bools(&terms[0].c, &terms[1].c))
+}
+fn mul_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_mul(dc, &terms[0], &terms[1])
+}
+
+/// Restoring divider returning `(quotient, remainder)`. A zero divisor
+/// yields an all-ones quotient and the dividend as remainder, as in SMT-LIB.
+fn udivrem_bits(tm: &mut TermManager, x: &[Term], y: &[Term]) -> (TermVec, TermVec) {
+    let len = x.len();
+    let f = tm.bool_const(false);
+    let mut ye = TermVec::from(y);
+    ye.push(f.clone());
+    let mut q = TermVec::from(vec![f.clone(); len]);
+    let mut r = TermVec::from(vec![f; len]);
+    for i in (0..len).rev() {
+        let mut shifted = TermVec::from([x[i].clone()]);
+        shifted.extend_from_slice(&r);
+        let diff = sub_bits(tm, &shifted, &ye);
+        q[i] = !&ult_bitblast(tm, &[shifted.clone(), ye.clone()])[0];
+        r = ite_bits(&q[i], &diff[..len], &shifted[..len]);
+    }
+    (q, r)
+}
+
+#[inline]
+fn abs_bits(tm: &mut TermManager, x: &[Term]) -> TermVec {
+    let nx = neg_bits(tm, x);
+    ite_bits(&x[x.len() - 1], &nx, x)
+}
+
+define_core_op!(Udiv, 2, bitblast: udiv_bitblast, simplify: udiv_simplify, eval: udiv_eval, bv_cnf_encode: udiv_bv_cnf_encode);
+fn udiv_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    let (x, y) = (&terms[0], &terms[1]);
+    if let Some(yc) = y.try_bv_const() {
+        if yc.is_zero() {
+            tm.record_rewrite("udiv1");
+            return TermResult::Some(x.mk_bv_const_ones());
+        }
+        if let Some(k) = yc.power_of_two() {
+            tm.record_rewrite("udiv2");
+            let k = tm.bv_const_from_usize(k, x.bv_len());
+            return TermResult::Some(x.op1(Srl, &k));
+        }
+    }
+    TermResult::None
+}
+fn udiv_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    udivrem_bits(tm, &terms[0], &terms[1]).0
+}
+fn udiv_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&udivrem_bools(&terms[0].c, &terms[1].c).0)
+}
+fn udiv_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_udivrem(dc, &terms[0], &terms[1]).0
+}
+
+define_core_op!(Urem, 2, bitblast: urem_bitblast, simplify: urem_simplify, eval: urem_eval, bv_cnf_encode: urem_bv_cnf_encode);
+fn urem_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    let (x, y) = (&terms[0], &terms[1]);
+    if let Some(yc) = y.try_bv_const() {
+        if yc.is_zero() {
+            tm.record_rewrite("urem1");
+            return TermResult::Some(x.clone());
+        }
+        if let Some(k) = yc.power_of_two() {
+            tm.record_rewrite("urem2");
+            let mask: Vec<bool> = (0..x.bv_len()).map(|i| i < k).collect();
+            let mask = tm.bv_const(BvConst::new(&mask));
+            return TermResult::Some(x & mask);
+        }
+    }
+    TermResult::None
+}
+fn urem_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    udivrem_bits(tm, &terms[0], &terms[1]).1
+}
+fn urem_eval(terms: &[BvConst]) -> BvConst {
+    BvConst::new(&udivrem_bools(&terms[0].c, &terms[1].c).1)
+}
+fn urem_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_udivrem(dc, &terms[0], &terms[1]).1
+}
+
+define_core_op!(Sdiv, 2, bitblast: sdiv_bitblast, simplify: sdiv_simplify, eval: sdiv_eval, bv_cnf_encode: sdiv_bv_cnf_encode);
+fn sdiv_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    let (x, y) = (&terms[0], &terms[1]);
+    if let Some(yc) = y.try_bv_const() {
+        if yc.is_zero() {
+            tm.record_rewrite("sdiv1");
+            let zero = x.mk_bv_const_zero();
+            let neg = x.op1(Slt, &zero);
+            return TermResult::Some(neg.ite(&x.mk_bv_const_one(), &x.mk_bv_const_ones()));
+        }
+        if yc.is_one() {
+            tm.record_rewrite("sdiv2");
+            return TermResult::Some(x.clone());
+        }
+    }
+    TermResult::None
+}
+fn sdiv_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let ax = abs_bits(tm, x);
+    let ay = abs_bits(tm, y);
+    let (q, _) = udivrem_bits(tm, &ax, &ay);
+    let nq = neg_bits(tm, &q);
+    let sign = &x[len - 1] ^ &y[len - 1];
+    ite_bits(&sign, &nq, &q)
+}
+fn sdiv_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (&terms[0].c, &terms[1].c);
+    let (q, _) = udivrem_bools(&abs_bools(x), &abs_bools(y));
+    if x[x.len() - 1] ^ y[y.len() - 1] {
+        BvConst::new(&neg_bools(&q))
+    } else {
+        BvConst::new(&q)
+    }
+}
+fn sdiv_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let ax = cnf_abs(dc, x);
+    let ay = cnf_abs(dc, y);
+    let (q, _) = cnf_udivrem(dc, &ax, &ay);
+    let nq = cnf_neg(dc, &q);
+    let sign = cnf_xor(dc, x[len - 1], y[len - 1]);
+    cnf_mux(dc, sign, &nq, &q)
+}
+
+define_core_op!(Srem, 2, bitblast: srem_bitblast, simplify: srem_simplify, eval: srem_eval, bv_cnf_encode: srem_bv_cnf_encode);
+fn srem_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    let (x, y) = (&terms[0], &terms[1]);
+    if let Some(yc) = y.try_bv_const() {
+        if yc.is_zero() {
+            tm.record_rewrite("srem1");
+            return TermResult::Some(x.clone());
+        }
+        if yc.is_one() {
+            tm.record_rewrite("srem2");
+            return TermResult::Some(x.mk_bv_const_zero());
+        }
+    }
+    TermResult::None
+}
+fn srem_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let ax = abs_bits(tm, x);
+    let ay = abs_bits(tm, y);
+    let (_, r) = udivrem_bits(tm, &ax, &ay);
+    let nr = neg_bits(tm, &r);
+    ite_bits(&x[len - 1], &nr, &r)
+}
+fn srem_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (&terms[0].c, &terms[1].c);
+    let (_, r) = udivrem_bools(&abs_bools(x), &abs_bools(y));
+    if x[x.len() - 1] {
+        BvConst::new(&neg_bools(&r))
+    } else {
+        BvConst::new(&r)
+    }
+}
+fn srem_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let ax = cnf_abs(dc, x);
+    let ay = cnf_abs(dc, y);
+    let (_, r) = cnf_udivrem(dc, &ax, &ay);
+    let nr = cnf_neg(dc, &r);
+    cnf_mux(dc, x[len - 1], &nr, &r)
+}
+
+define_core_op!(Smod, 2, bitblast: smod_bitblast, simplify: smod_simplify, eval: smod_eval, bv_cnf_encode: smod_bv_cnf_encode);
+fn smod_simplify(tm: &mut TermManager, terms: &[Term]) -> TermResult {
+    let (x, y) = (&terms[0], &terms[1]);
+    if let Some(yc) = y.try_bv_const() {
+        if yc.is_zero() {
+            tm.record_rewrite("smod1");
+            return TermResult::Some(x.clone());
+        }
+        if yc.is_one() {
+            tm.record_rewrite("smod2");
+            return TermResult::Some(x.mk_bv_const_zero());
+        }
+    }
+    TermResult::None
+}
+fn smod_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let ax = abs_bits(tm, x);
+    let ay = abs_bits(tm, y);
+    let (_, u) = udivrem_bits(tm, &ax, &ay);
+    let nu = neg_bits(tm, &u);
+    let srem = ite_bits(&x[len - 1], &nu, &u);
+    let c = tm.bool_const(false);
+    let (adj, _) = ripple_carry_adder(tm, &srem, y, c);
+    let nonzero = tm.new_op_terms_fold(Or, u.iter());
+    let fix = (&x[len - 1] ^ &y[len - 1]) & nonzero;
+    ite_bits(&fix, &adj, &srem)
+}
+fn smod_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (&terms[0].c, &terms[1].c);
+    let (xs, ys) = (x[x.len() - 1], y[y.len() - 1]);
+    let (_, u) = udivrem_bools(&abs_bools(x), &abs_bools(y));
+    let srem = if xs { neg_bools(&u) } else { u.clone() };
+    if xs != ys && u.iter().any(|b| *b) {
+        BvConst::new(&add_bools(&srem, y, false))
+    } else {
+        BvConst::new(&srem)
+    }
+}
+fn smod_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let ax = cnf_abs(dc, x);
+    let ay = cnf_abs(dc, y);
+    let (_, u) = cnf_udivrem(dc, &ax, &ay);
+    let nu = cnf_neg(dc, &u);
+    let srem = cnf_mux(dc, x[len - 1], &nu, &u);
+    let (adj, _) = cnf_adder(dc, &srem, y, Lit::constant(false));
+    let nonzero = cnf_or_n(dc, &u);
+    let sign = cnf_xor(dc, x[len - 1], y[len - 1]);
+    let fix = cnf_and(dc, sign, nonzero);
+    cnf_mux(dc, fix, &adj, &srem)
+}
+
+define_core_op!(Uaddo, 2, sort: bool_sort, bitblast: uaddo_bitblast, eval: uaddo_eval, bv_cnf_encode: uaddo_bv_cnf_encode);
+fn uaddo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let c = tm.bool_const(false);
+    TermVec::from([ripple_carry_adder(tm, &terms[0], &terms[1], c).1])
+}
+fn uaddo_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (
+        widen(&terms[0].c, false, false),
+        widen(&terms[1].c, false, false),
+    );
+    BvConst::new(&[add_bools(&x, &y, false)[terms[0].len()]])
+}
+fn uaddo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    LitVec::from([cnf_adder(dc, &terms[0], &terms[1], Lit::constant(false)).1])
+}
+
+define_core_op!(Saddo, 2, sort: bool_sort, bitblast: saddo_bitblast, eval: saddo_eval, bv_cnf_encode: saddo_bv_cnf_encode);
+fn saddo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let c = tm.bool_const(false);
+    let (r, _) = ripple_carry_adder(tm, x, y, c);
+    let (xs, ys, rs) = (&x[len - 1], &y[len - 1], &r[len - 1]);
+    TermVec::from([xs.op1(Eq, ys) & (xs ^ rs)])
+}
+fn saddo_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (&terms[0].c, &terms[1].c);
+    let len = x.len();
+    let r = add_bools(x, y, false);
+    BvConst::new(&[x[len - 1] == y[len - 1] && x[len - 1] != r[len - 1]])
+}
+fn saddo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let (r, _) = cnf_adder(dc, x, y, Lit::constant(false));
+    let same = !cnf_xor(dc, x[len - 1], y[len - 1]);
+    let flip = cnf_xor(dc, x[len - 1], r[len - 1]);
+    LitVec::from([cnf_and(dc, same, flip)])
+}
+
+define_core_op!(Usubo, 2, sort: bool_sort, bitblast: usubo_bitblast, eval: usubo_eval, bv_cnf_encode: usubo_bv_cnf_encode);
+fn usubo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    ult_bitblast(tm, terms)
+}
+fn usubo_eval(terms: &[BvConst]) -> BvConst {
+    ult_eval(terms)
+}
+fn usubo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    LitVec::from([cnf_ult(dc, &terms[0], &terms[1])])
+}
+
+define_core_op!(Ssubo, 2, sort: bool_sort, bitblast: ssubo_bitblast, eval: ssubo_eval, bv_cnf_encode: ssubo_bv_cnf_encode);
+fn ssubo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let r = sub_bits(tm, x, y);
+    let (xs, ys, rs) = (&x[len - 1], &y[len - 1], &r[len - 1]);
+    TermVec::from([(xs ^ ys) & (xs ^ rs)])
+}
+fn ssubo_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (&terms[0].c, &terms[1].c);
+    let len = x.len();
+    let r = add_bools(x, &neg_bools(y), false);
+    BvConst::new(&[x[len - 1] != y[len - 1] && x[len - 1] != r[len - 1]])
+}
+fn ssubo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let (r, _) = cnf_sub(dc, x, y);
+    let diff = cnf_xor(dc, x[len - 1], y[len - 1]);
+    let flip = cnf_xor(dc, x[len - 1], r[len - 1]);
+    LitVec::from([cnf_and(dc, diff, flip)])
+}
 
-define_core_op!(Read, 2, sort: read_sort, bitblast: read_bitblast);
+define_core_op!(Umulo, 2, sort: bool_sort, bitblast: umulo_bitblast, eval: umulo_eval, bv_cnf_encode: umulo_bv_cnf_encode);
+fn umulo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let len = terms[0].len();
+    let f = tm.bool_const(false);
+    let x = TermVec::from(widen(&terms[0], false, f.clone()).as_slice());
+    let y = TermVec::from(widen(&terms[1], false, f).as_slice());
+    let p = mul_bitblast(tm, &[x, y]);
+    TermVec::from([tm.new_op_terms_fold(Or, p.iter().skip(len))])
+}
+fn umulo_eval(terms: &[BvConst]) -> BvConst {
+    let len = terms[0].len();
+    let p = mul_bools(
+        &widen(&terms[0].c, false, false),
+        &widen(&terms[1].c, false, false),
+    );
+    BvConst::new(&[p[len..].iter().any(|b| *b)])
+}
+fn umulo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let len = terms[0].len();
+    let f = Lit::constant(false);
+    let p = cnf_mul(dc, &widen(&terms[0], false, f), &widen(&terms[1], false, f));
+    LitVec::from([cnf_or_n(dc, &p[len..])])
+}
+
+define_core_op!(Smulo, 2, sort: bool_sort, bitblast: smulo_bitblast, eval: smulo_eval, bv_cnf_encode: smulo_bv_cnf_encode);
+fn smulo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let len = terms[0].len();
+    let f = tm.bool_const(false);
+    let x = TermVec::from(widen(&terms[0], true, f.clone()).as_slice());
+    let y = TermVec::from(widen(&terms[1], true, f).as_slice());
+    let p = mul_bitblast(tm, &[x, y]);
+    let s = &p[len - 1];
+    let diff: TermVec = p.iter().skip(len).map(|b| b ^ s).collect();
+    TermVec::from([tm.new_op_terms_fold(Or, diff.iter())])
+}
+fn smulo_eval(terms: &[BvConst]) -> BvConst {
+    let len = terms[0].len();
+    let p = mul_bools(
+        &widen(&terms[0].c, true, false),
+        &widen(&terms[1].c, true, false),
+    );
+    BvConst::new(&[p[len..].iter().any(|b| *b != p[len - 1])])
+}
+fn smulo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let len = terms[0].len();
+    let f = Lit::constant(false);
+    let p = cnf_mul(dc, &widen(&terms[0], true, f), &widen(&terms[1], true, f));
+    let diff: Vec<Lit> = p[len..]
+        .iter()
+        .map(|b| cnf_xor(dc, *b, p[len - 1]))
+        .collect();
+    LitVec::from([cnf_or_n(dc, &diff)])
+}
+
+define_core_op!(Sdivo, 2, sort: bool_sort, bitblast: sdivo_bitblast, eval: sdivo_eval, bv_cnf_encode: sdivo_bv_cnf_encode);
+fn sdivo_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let mut min: TermVec = x[..len - 1].iter().map(|b| !b).collect();
+    min.push(x[len - 1].clone());
+    let bits: Vec<&Term> = min.iter().chain(y.iter()).collect();
+    TermVec::from([tm.new_op_terms_fold(And, bits)])
+}
+fn sdivo_eval(terms: &[BvConst]) -> BvConst {
+    let (x, y) = (&terms[0].c, &terms[1].c);
+    let len = x.len();
+    let min = x[len - 1] && x[..len - 1].iter().all(|b| !b);
+    BvConst::new(&[min && y.iter().all(|b| *b)])
+}
+fn sdivo_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let mut bits: Vec<Lit> = x[..len - 1].iter().map(|b| !*b).collect();
+    bits.push(x[len - 1]);
+    bits.extend_from_slice(y);
+    LitVec::from([cnf_and_n(dc, &bits)])
+}
+
+define_core_op!(Read, 2, sort: read_sort, bitblast: read_bitblast, eval: read_eval, bv_cnf_encode: read_bv_cnf_encode);
 fn read_sort(terms: &[Term]) -> Sort {
     let (_, e) = terms[0].sort().array();
     Sort::Bv(e)
@@ -596,12 +1537,12 @@ fn onehot_encode(tm: &mut TermManager, x: &[Term]) -> TermVec {
     for sb in 0..x.len() {
         let ss = 1 << sb;
         let shift = &x[sb];
+        for j in (ss..len).rev() {
+            res[j] = shift.ite(&res[j - ss], &res[j]);
+        }
         for j in 0..ss {
             res[j] = &!shift & &res[j];
         }
-        for j in ss..len {
-            res[j] = shift.ite(&res[j - ss], &res[j]);
-        }
     }
     TermVec::from(res.as_slice())
 }
@@ -623,8 +1564,17 @@ fn read_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn read_eval(terms: &[BvConst]) -> BvConst {
+    let (array, index) = (&terms[0].c, &terms[1].c);
+    let element_len = array.len() >> index.len();
+    let i = shift_amount(index);
+    BvConst::new(&array[element_len * i..element_len * (i + 1)])
+}
+fn read_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_read(dc, &terms[0], &terms[1])
+}
 
-define_core_op!(Write, 3, bitblast: write_bitblast);
+define_core_op!(Write, 3, bitblast: write_bitblast, eval: write_eval, bv_cnf_encode: write_bv_cnf_encode);
 fn write_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     let (array, index, value) = (&terms[0], &terms[1], &terms[2]);
     let index_len = index.len();
@@ -641,3 +1591,14 @@ fn write_bitblast(tm: &mut TermManager, terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn write_eval(terms: &[BvConst]) -> BvConst {
+    let (array, index, value) = (&terms[0].c, &terms[1].c, &terms[2].c);
+    let element_len = array.len() >> index.len();
+    let i = shift_amount(index);
+    let mut res = array.clone();
+    res[element_len * i..element_len * (i + 1)].copy_from_slice(value);
+    BvConst::new(&res)
+}
+fn write_bv_cnf_encode(dc: &mut DagCnf, terms: &[LitVec]) -> LitVec {
+    cnf_write(dc, &terms[0], &terms[1], &terms[2])
+}
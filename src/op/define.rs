@@ -24,6 +24,13 @@ macro_rules! op_trait_impl {
             $impl(tm, terms)
         }
     };
+    (eval $impl:expr) => {
+        #[inline]
+        fn eval(&self, terms: &[crate::BvConst]) -> crate::BvConst {
+            debug_assert!(self.num_operand() == terms.len());
+            $impl(terms)
+        }
+    };
     (bitblast $impl:expr) => {
         #[inline]
         fn bitblast(
@@ -46,6 +53,58 @@ macro_rules! op_trait_impl {
             $impl(dc, terms)
         }
     };
+    (cnf_encode_polarity $impl:expr) => {
+        #[inline]
+        fn cnf_encode_polarity(
+            &self,
+            dc: &mut logic_form::DagCnf,
+            terms: &[logic_form::Lit],
+            polarity: crate::bitblast::Polarity,
+        ) -> logic_form::Lit {
+            debug_assert!(self.num_operand() == terms.len());
+            $impl(dc, terms, polarity)
+        }
+    };
+    (operand_polarity $impl:expr) => {
+        #[inline]
+        fn operand_polarity(
+            &self,
+            polarity: crate::bitblast::Polarity,
+            i: usize,
+        ) -> crate::bitblast::Polarity {
+            $impl(polarity, i)
+        }
+    };
+    (bv_cnf_encode $impl:expr) => {
+        #[inline]
+        fn bv_cnf_encode(
+            &self,
+            dc: &mut logic_form::DagCnf,
+            terms: &[logic_form::LitVec],
+        ) -> logic_form::LitVec {
+            debug_assert!(self.num_operand() == terms.len());
+            $impl(dc, terms)
+        }
+    };
+}
+
+macro_rules! op_dyn_impl {
+    () => {
+        #[inline]
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        #[inline]
+        fn dyn_eq(&self, other: &dyn crate::op::Op) -> bool {
+            other.as_any().downcast_ref::<Self>() == Some(self)
+        }
+
+        #[inline]
+        fn dyn_hash(&self, mut state: &mut dyn std::hash::Hasher) {
+            std::hash::Hash::hash(self, &mut state)
+        }
+    };
 }
 
 macro_rules! define_core_op {
@@ -54,6 +113,8 @@ macro_rules! define_core_op {
         pub struct $name;
         inventory::submit! {crate::op::DynOpCollect(|| crate::op::DynOp::new($name))}
         impl crate::op::Op for $name {
+            crate::op::define::op_dyn_impl!();
+
             #[inline]
             fn num_operand(&self) -> usize {
                 $num_operand
@@ -82,6 +143,8 @@ macro_rules! define_non_core_op {
         pub struct $name;
         inventory::submit! {crate::op::DynOpCollect(|| crate::op::DynOp::new($name))}
         impl crate::op::Op for $name {
+            crate::op::define::op_dyn_impl!();
+
             #[inline]
             fn num_operand(&self) -> usize {
                 $num_operand
@@ -93,6 +156,8 @@ macro_rules! define_non_core_op {
         pub struct $name;
         inventory::submit! {crate::op::DynOpCollect(|| crate::op::DynOp::new($name))}
         impl crate::op::Op for $name {
+            crate::op::define::op_dyn_impl!();
+
             #[inline]
             fn num_operand(&self) -> usize {
                 $num_operand
@@ -114,4 +179,5 @@ macro_rules! define_non_core_op {
 
 pub(crate) use define_core_op;
 pub(crate) use define_non_core_op;
+pub(crate) use op_dyn_impl;
 pub(crate) use op_trait_impl;
@@ -1,13 +1,15 @@
+mod cnf;
 mod core_op;
 mod define;
 mod other_op;
 
 use super::term::Term;
-use crate::{Sort, TermManager, TermResult, TermVec};
+use crate::bitblast::Polarity;
+use crate::{BvConst, Sort, TermManager, TermResult, TermVec};
 pub use core_op::*;
 use giputils::hash::GHashMap;
 use lazy_static::lazy_static;
-use logic_form::{DagCnf, Lit};
+use logic_form::{DagCnf, Lit, LitVec};
 pub use other_op::*;
 use std::fmt;
 use std::{
@@ -16,10 +18,10 @@ use std::{
     fmt::Debug,
     hash::{Hash, Hasher},
     ops::Deref,
-    rc::Rc,
+    sync::Arc,
 };
 
-pub trait Op: Debug + 'static {
+pub trait Op: Debug + Send + Sync + 'static {
     #[inline]
     fn type_id(&self) -> TypeId {
         TypeId::of::<Self>()
@@ -37,6 +39,14 @@ pub trait Op: Debug + 'static {
 
     fn num_operand(&self) -> usize;
 
+    fn as_any(&self) -> &dyn std::any::Any;
+
+    /// Equality of ops including their parameters.
+    fn dyn_eq(&self, other: &dyn Op) -> bool;
+
+    /// Hashes the parameters of the op.
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+
     #[inline]
     fn sort(&self, terms: &[Term]) -> Sort {
         terms[0].sort()
@@ -50,6 +60,10 @@ pub trait Op: Debug + 'static {
         TermResult::None
     }
 
+    fn eval(&self, _terms: &[BvConst]) -> BvConst {
+        panic!("{:?} not support eval", self);
+    }
+
     fn bitblast(&self, _tm: &mut TermManager, _terms: &[TermVec]) -> TermVec {
         panic!("{:?} not support biblast", self);
     }
@@ -57,17 +71,39 @@ pub trait Op: Debug + 'static {
     fn cnf_encode(&self, _dc: &mut DagCnf, _terms: &[Lit]) -> Lit {
         panic!("{:?} not support cnf_encode", self);
     }
+
+    /// Encodes only the directions of the definition needed for the
+    /// `polarity` of the term, by default the full definition.
+    fn cnf_encode_polarity(&self, dc: &mut DagCnf, terms: &[Lit], _polarity: Polarity) -> Lit {
+        self.cnf_encode(dc, terms)
+    }
+
+    /// The polarity of the `i`-th operand of a term occurring in `polarity`.
+    #[inline]
+    fn operand_polarity(&self, _polarity: Polarity, _i: usize) -> Polarity {
+        Polarity::Both
+    }
+
+    fn bv_cnf_encode(&self, _dc: &mut DagCnf, _terms: &[LitVec]) -> LitVec {
+        panic!("{:?} not support bv_cnf_encode", self);
+    }
 }
 
 #[derive(Clone)]
 pub struct DynOp {
-    op: Rc<dyn Op>,
+    op: Arc<dyn Op>,
 }
 
 impl DynOp {
     #[inline]
     pub fn new(op: impl Op) -> Self {
-        Self { op: Rc::new(op) }
+        Self { op: Arc::new(op) }
+    }
+
+    /// Returns the concrete op, e.g. to read the bounds of a `Slice`.
+    #[inline]
+    pub fn downcast_ref<T: Op>(&self) -> Option<&T> {
+        self.op.as_any().downcast_ref()
     }
 }
 
@@ -98,13 +134,14 @@ impl Hash for DynOp {
     #[inline]
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.op.type_id().hash(state);
+        self.op.dyn_hash(state);
     }
 }
 
 impl PartialEq for DynOp {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.op.type_id() == other.op.type_id()
+        self.op.dyn_eq(other.op.deref())
     }
 }
 
@@ -113,18 +150,19 @@ impl std::cmp::Eq for DynOp {}
 impl<O: Op> PartialEq<O> for DynOp {
     #[inline]
     fn eq(&self, other: &O) -> bool {
-        self.op.type_id() == other.type_id()
+        self.op.dyn_eq(other)
     }
 }
 
-unsafe impl Send for DynOp {}
-
-unsafe impl Sync for DynOp {}
-
 struct DynOpCollect(fn() -> DynOp);
 
 inventory::collect!(DynOpCollect);
 
+/// An op carrying parameters, built from its lowercase name and parameters.
+struct ParamOpCollect(&'static str, fn(&[usize]) -> Option<DynOp>);
+
+inventory::collect!(ParamOpCollect);
+
 lazy_static! {
     static ref OP_MAP: GHashMap<String, DynOp> = {
         let mut m = GHashMap::new();
@@ -134,37 +172,67 @@ lazy_static! {
         }
         m
     };
+    static ref PARAM_OP_MAP: GHashMap<&'static str, &'static ParamOpCollect> =
+        inventory::iter::<ParamOpCollect>
+            .into_iter()
+            .map(|op| (op.0, op))
+            .collect();
+}
+
+impl DynOp {
+    /// Looks up an op by its case-insensitive name. Ops carrying parameters
+    /// take them after the name separated by whitespace, e.g. `slice 7 0`,
+    /// `sext 4` or `uext 4`, and are not found without them.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let mut tok = name.split_whitespace();
+        let name = tok.next()?.to_lowercase();
+        let params: Vec<usize> = tok.map(|p| p.parse().ok()).collect::<Option<_>>()?;
+        if params.is_empty()
+            && let Some(op) = OP_MAP.get(&name)
+        {
+            return Some(op.clone());
+        }
+        (PARAM_OP_MAP.get(name.as_str())?.1)(&params)
+    }
+
+    /// Checks the operand sorts of a core op looked up by name before
+    /// building it from read input: arrays are only accessed by indices and
+    /// elements of their sorts, `ite` takes a boolean condition and branches
+    /// of one sort, `concat` takes bit-vectors and every other op bit-vectors
+    /// of one sort, or arrays of one sort for `eq` and `neq`.
+    pub(crate) fn well_sorted(&self, terms: &[Term]) -> bool {
+        if terms.len() != self.num_operand() {
+            return false;
+        }
+        let sorts: Vec<Sort> = terms.iter().map(|t| t.sort()).collect();
+        if *self == Read || *self == Write {
+            match sorts[0] {
+                Sort::Array(i, e) => {
+                    sorts[1] == Sort::Bv(i) && (*self == Read || sorts[2] == Sort::Bv(e))
+                }
+                Sort::Bv(_) => false,
+            }
+        } else if *self == Ite {
+            sorts[0].is_bool() && sorts[1] == sorts[2]
+        } else if *self == Concat {
+            sorts.iter().all(|s| !s.is_array())
+        } else {
+            sorts.iter().all(|s| *s == sorts[0])
+                && (*self == Eq || *self == Neq || !sorts[0].is_array())
+        }
+    }
 }
 
 impl From<&str> for DynOp {
+    /// See [`DynOp::from_name`], panics on unknown ops.
     #[inline]
     fn from(value: &str) -> Self {
-        OP_MAP
-            .get(&value.to_lowercase())
-            .unwrap_or_else(|| panic!("unsupport {value} op!"))
-            .clone()
+        Self::from_name(value).unwrap_or_else(|| panic!("unsupport {value} op!"))
     }
 }
 
 // pub enum BiOpType {
-//     Iff,
-//     Nand,
-//     Nor,
-//     Rol,
-//     Ror,
-//     Sdiv,
-//     Udiv,
-//     Smod,
-//     Srem,
-//     Urem,
-//     Saddo,
-//     Uaddo,
-//     Sdivo,
 //     Udivo,
-//     Smulo,
-//     Umulo,
-//     Ssubo,
-//     Usubo,
 //     Read,
 // }
 
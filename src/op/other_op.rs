@@ -1,6 +1,6 @@
-use super::define::define_non_core_op;
-use super::{Concat, Eq, Slt, Ult, Xor};
-use crate::{Term, TermManager};
+use super::define::{define_non_core_op, op_dyn_impl};
+use super::{And, Concat, Eq, Op, Or, Slt, Ult, Xor};
+use crate::{Sort, Term, TermManager};
 
 define_non_core_op!(Neg, 1, neg_normalize);
 fn neg_normalize(_tm: &mut TermManager, terms: &[Term]) -> Term {
@@ -45,12 +45,50 @@ fn xnor_normalize(tm: &mut TermManager, terms: &[Term]) -> Term {
     !tm.new_op_term(Xor, terms)
 }
 
-define_non_core_op!(Uext, 2, uext_normalize);
-fn uext_normalize(tm: &mut TermManager, terms: &[Term]) -> Term {
-    if terms[1].bv_len() == 0 {
-        terms[0].clone()
-    } else {
-        tm.new_op_term(Concat, &[terms[1].clone(), terms[0].clone()])
+define_non_core_op!(Nand, 2, nand_normalize);
+fn nand_normalize(tm: &mut TermManager, terms: &[Term]) -> Term {
+    !tm.new_op_term(And, terms)
+}
+
+define_non_core_op!(Nor, 2, nor_normalize);
+fn nor_normalize(tm: &mut TermManager, terms: &[Term]) -> Term {
+    !tm.new_op_term(Or, terms)
+}
+
+define_non_core_op!(Iff, 2, iff_normalize);
+fn iff_normalize(tm: &mut TermManager, terms: &[Term]) -> Term {
+    tm.new_op_term(Eq, terms)
+}
+
+/// Zero extension by the given number of bits.
+#[derive(Hash, Debug, PartialEq, Clone, Copy)]
+pub struct Uext(pub usize);
+
+inventory::submit! {crate::op::ParamOpCollect("uext", |p| match p {
+    [ext] => Some(Uext(*ext).into()),
+    _ => None,
+})}
+
+impl Op for Uext {
+    op_dyn_impl!();
+
+    #[inline]
+    fn num_operand(&self) -> usize {
+        1
+    }
+
+    #[inline]
+    fn sort(&self, _terms: &[Term]) -> Sort {
+        panic!("{:?} not support sort", self);
+    }
+
+    fn normalize(&self, tm: &mut TermManager, terms: &[Term]) -> Term {
+        if self.0 == 0 {
+            terms[0].clone()
+        } else {
+            let zero = tm.bv_const_zero(self.0);
+            tm.new_op_term(Concat, [&zero, &terms[0]])
+        }
     }
 }
 
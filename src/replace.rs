@@ -1,16 +1,36 @@
-use crate::{Term, TermType};
-use std::ops::Deref;
+use crate::{Term, TermMap, TermVec};
+use giputils::hash::GHashMap;
+
+struct Substitutor<'a> {
+    map: &'a GHashMap<Term, Term>,
+}
+
+impl TermMap for Substitutor<'_> {
+    #[inline]
+    fn lookup(&mut self, term: &Term) -> Option<Term> {
+        self.map.get(term).cloned()
+    }
+}
 
 impl Term {
     pub fn replace(&self, x: &Term, y: &Term) -> Term {
-        if self.eq(x) {
-            return y.clone();
-        }
-        let TermType::Op(op) = self.deref() else {
-            return self.clone();
-        };
-        let terms: Vec<_> = op.terms.iter().map(|t| t.replace(x, y)).collect();
-        let mut tm = self.get_manager();
-        tm.new_op_term(op.op.clone(), &terms)
+        let map = GHashMap::from_iter([(x.clone(), y.clone())]);
+        self.substitute(&map, &mut GHashMap::new())
+    }
+
+    /// Replaces every subterm that is a key of `map` by its value at once,
+    /// the replacements are not substituted again. `cache` memoizes results
+    /// and may be shared between calls with the same `map`.
+    #[inline]
+    pub fn substitute(&self, map: &GHashMap<Term, Term>, cache: &mut GHashMap<Term, Term>) -> Term {
+        self.map(&mut Substitutor { map }, cache)
+    }
+}
+
+impl TermVec {
+    /// Substitutes `map` in every term, sharing one cache over all of them.
+    pub fn substitute(&self, map: &GHashMap<Term, Term>) -> TermVec {
+        let mut cache = GHashMap::new();
+        self.iter().map(|t| t.substitute(map, &mut cache)).collect()
     }
 }
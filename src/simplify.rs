@@ -1,22 +1,102 @@
-use crate::{Term, TermManager, TermResult};
+use crate::{OpTerm, Term, TermManager, TermMap, TermResult};
 use giputils::hash::GHashMap;
+use std::fmt::{self, Display};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Simplifier<'a> {
+    tm: &'a mut TermManager,
+}
+
+impl TermMap for Simplifier<'_> {
+    fn map_op(&mut self, _term: &Term, op: &OpTerm, terms: &[Term]) -> Term {
+        if let Some(c) = self.tm.try_eval_const(&op.op, terms) {
+            c
+        } else if let TermResult::Some(new) = op.op.simplify(self.tm, terms) {
+            new
+        } else {
+            self.tm.new_op_term(op.op.clone(), terms)
+        }
+    }
+}
 
 impl Term {
+    #[inline]
     pub fn simplify(&self, tm: &mut TermManager, map: &mut GHashMap<Term, Term>) -> Term {
-        if let Some(res) = map.get(self) {
-            return res.clone();
+        self.map(&mut Simplifier { tm }, map)
+    }
+}
+
+/// Number of applications of each named simplification rule.
+#[derive(Clone, Debug, Default)]
+pub struct RewriteStats {
+    count: GHashMap<&'static str, usize>,
+}
+
+impl RewriteStats {
+    #[inline]
+    pub fn get(&self, rule: &str) -> usize {
+        self.count.get(rule).copied().unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.count.values().sum()
+    }
+
+    /// Rules that fired, most frequent first.
+    pub fn sorted(&self) -> Vec<(&'static str, usize)> {
+        let mut res: Vec<_> = self.count.iter().map(|(r, c)| (*r, *c)).collect();
+        res.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        res
+    }
+}
+
+impl Display for RewriteStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (rule, count) in self.sorted() {
+            writeln!(f, "{rule}: {count}")?;
         }
-        let simp = if let Some(op_term) = self.try_op_term() {
-            let terms: Vec<Term> = op_term.terms.iter().map(|s| s.simplify(tm, map)).collect();
-            if let TermResult::Some(new) = op_term.op.simplify(tm, &terms) {
-                new
-            } else {
-                tm.new_op_term(op_term.op.clone(), &terms)
-            }
-        } else {
-            self.clone()
-        };
-        map.insert(self.clone(), simp);
-        map.get(self).unwrap().clone()
+        write!(f, "total: {}", self.total())
+    }
+}
+
+/// Shared counters behind [`RewriteStats`]. A rule takes the write lock only
+/// the first time it fires, later applications bump its atomic counter under
+/// the read lock, so concurrent simplifications do not serialize.
+#[derive(Default)]
+pub(crate) struct RewriteCounters {
+    count: RwLock<GHashMap<&'static str, AtomicUsize>>,
+}
+
+impl RewriteCounters {
+    #[inline]
+    pub(crate) fn record(&self, rule: &'static str) {
+        if let Some(c) = self.count.read().unwrap().get(rule) {
+            c.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.count
+            .write()
+            .unwrap()
+            .entry(rule)
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn stats(&self) -> RewriteStats {
+        let count = self.count.read().unwrap();
+        RewriteStats {
+            count: count
+                .iter()
+                .map(|(r, c)| (*r, c.load(Ordering::Relaxed)))
+                .filter(|(_, c)| *c > 0)
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn reset(&self) {
+        self.count.write().unwrap().clear();
     }
 }
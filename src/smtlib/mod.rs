@@ -0,0 +1,5 @@
+mod parser;
+mod printer;
+
+pub use parser::*;
+pub use printer::*;
@@ -0,0 +1,646 @@
+use crate::op::{And, Concat, DynOp, Eq, Neq};
+use crate::{BvConst, ParseError, Sort, Term, TermManager, TermVec};
+use giputils::hash::GHashMap;
+use std::{fs, path::Path};
+
+#[derive(Clone, Debug)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    #[inline]
+    fn atom(&self) -> Result<&str, ParseError> {
+        match self {
+            Sexp::Atom(a) => Ok(a),
+            Sexp::List(_) => Err(ParseError::syntax(format!(
+                "expected symbol, found {self:?}"
+            ))),
+        }
+    }
+
+    #[inline]
+    fn list(&self) -> Result<&[Sexp], ParseError> {
+        match self {
+            Sexp::List(l) => Ok(l),
+            Sexp::Atom(a) => Err(ParseError::syntax(format!("expected list, found {a}"))),
+        }
+    }
+
+    #[inline]
+    fn numeral(&self) -> Result<usize, ParseError> {
+        let a = self.atom()?;
+        a.parse()
+            .map_err(|_| ParseError::syntax(format!("expected numeral, found {a}")))
+    }
+}
+
+/// Checks that the list `l` headed by a symbol has exactly `len` elements.
+fn expect_len(l: &[Sexp], len: usize) -> Result<(), ParseError> {
+    if l.len() == len {
+        return Ok(());
+    }
+    let head = l.first().and_then(|h| h.atom().ok()).unwrap_or("()");
+    Err(ParseError::syntax(format!(
+        "expected {} arguments of {head}, found {}",
+        len - 1,
+        l.len().saturating_sub(1)
+    )))
+}
+
+fn parse_sexps(src: &str) -> Result<Vec<Sexp>, ParseError> {
+    let bytes = src.as_bytes();
+    let mut stack: Vec<Vec<Sexp>> = vec![Vec::new()];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b';' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'(' => stack.push(Vec::new()),
+            b')' => {
+                if stack.len() == 1 {
+                    return Err(ParseError::syntax(format!("unbalanced ')' at byte {i}")));
+                }
+                let l = stack.pop().unwrap();
+                stack.last_mut().unwrap().push(Sexp::List(l));
+            }
+            b'|' => {
+                let start = i + 1;
+                let Some(len) = bytes[start..].iter().position(|b| *b == b'|') else {
+                    return Err(ParseError::syntax(format!(
+                        "unterminated quoted symbol at byte {i}"
+                    )));
+                };
+                i = start + len;
+                let atom = src[start..i].to_string();
+                stack.last_mut().unwrap().push(Sexp::Atom(atom));
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None => {
+                            return Err(ParseError::syntax(format!(
+                                "unterminated string literal at byte {start}"
+                            )));
+                        }
+                        Some(b'"') if bytes.get(i + 1) == Some(&b'"') => i += 2,
+                        Some(b'"') => break,
+                        Some(_) => i += 1,
+                    }
+                }
+                let atom = src[start..=i].to_string();
+                stack.last_mut().unwrap().push(Sexp::Atom(atom));
+            }
+            c if c.is_ascii_whitespace() => (),
+            _ => {
+                let start = i;
+                while i + 1 < bytes.len()
+                    && !bytes[i + 1].is_ascii_whitespace()
+                    && !matches!(bytes[i + 1], b'(' | b')' | b';' | b'|' | b'"')
+                {
+                    i += 1;
+                }
+                let atom = src[start..=i].to_string();
+                stack.last_mut().unwrap().push(Sexp::Atom(atom));
+            }
+        }
+        i += 1;
+    }
+    if stack.len() != 1 {
+        return Err(ParseError::syntax("unbalanced '(' at end of input"));
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// Resolves an SMT-LIB function symbol to an op through the op name map.
+fn smtlib_op(name: &str) -> Option<DynOp> {
+    let name = match name {
+        "bvule" => "ulte",
+        "bvuge" => "ugte",
+        "bvsle" => "slte",
+        "bvsge" => "sgte",
+        "bvshl" => "sll",
+        "bvlshr" => "srl",
+        "bvashr" => "sra",
+        "bvcomp" => "eq",
+        "select" => "read",
+        "store" => "write",
+        "=>" => "implies",
+        _ => name.strip_prefix("bv").unwrap_or(name),
+    };
+    DynOp::from_name(name)
+}
+
+fn check_sorts(name: &str, op: &DynOp, args: &[Term]) -> Result<(), ParseError> {
+    if op.well_sorted(args) {
+        return Ok(());
+    }
+    let sorts: Vec<Sort> = args.iter().map(|a| a.sort()).collect();
+    Err(ParseError::syntax(format!(
+        "ill-sorted application of {name} to {sorts:?}"
+    )))
+}
+
+#[derive(Clone)]
+struct FunDef {
+    params: Vec<(String, Sort)>,
+    sort: Sort,
+    body: Sexp,
+}
+
+pub struct Smtlib {
+    pub vars: GHashMap<String, Term>,
+    pub assertions: TermVec,
+}
+
+impl Smtlib {
+    /// Parses a QF_BV or QF_ABV script, creating its vars in `tm`. Commands
+    /// that only control the solver, such as `check-sat` or `get-model`, are
+    /// ignored, `push` and `pop` scope the declarations and assertions.
+    pub fn parse(tm: &mut TermManager, src: &str) -> Result<Self, ParseError> {
+        let mut parser = SmtlibParser {
+            tm,
+            vars: GHashMap::new(),
+            defs: GHashMap::new(),
+            funs: GHashMap::new(),
+            scopes: Vec::new(),
+            expanding: Vec::new(),
+            assertions: TermVec::new(),
+            levels: Vec::new(),
+        };
+        for cmd in parse_sexps(src)? {
+            parser.command(&cmd)?;
+        }
+        Ok(Self {
+            vars: parser.vars,
+            assertions: parser.assertions,
+        })
+    }
+
+    pub fn from_file(tm: &mut TermManager, path: impl AsRef<Path>) -> Result<Self, ParseError> {
+        let src = fs::read_to_string(path)?;
+        Self::parse(tm, &src)
+    }
+}
+
+/// The declarations and assertions of an assertion level, restored by `pop`.
+struct Level {
+    vars: GHashMap<String, Term>,
+    defs: GHashMap<String, Term>,
+    funs: GHashMap<String, FunDef>,
+    num_assertion: usize,
+}
+
+struct SmtlibParser<'a> {
+    tm: &'a mut TermManager,
+    vars: GHashMap<String, Term>,
+    defs: GHashMap<String, Term>,
+    funs: GHashMap<String, FunDef>,
+    scopes: Vec<GHashMap<String, Term>>,
+    /// Functions being expanded, to reject recursive definitions.
+    expanding: Vec<String>,
+    assertions: TermVec,
+    levels: Vec<Level>,
+}
+
+impl SmtlibParser<'_> {
+    fn command(&mut self, cmd: &Sexp) -> Result<(), ParseError> {
+        let cmd = cmd.list()?;
+        let Some(name) = cmd.first() else {
+            return Err(ParseError::syntax("empty command"));
+        };
+        match name.atom()? {
+            "set-logic"
+            | "set-info"
+            | "set-option"
+            | "check-sat"
+            | "check-sat-assuming"
+            | "get-model"
+            | "get-value"
+            | "get-info"
+            | "get-option"
+            | "get-assertions"
+            | "get-assignment"
+            | "get-proof"
+            | "get-unsat-core"
+            | "get-unsat-assumptions"
+            | "echo"
+            | "exit" => (),
+            "push" => {
+                for _ in 0..Self::level(cmd)? {
+                    self.levels.push(Level {
+                        vars: self.vars.clone(),
+                        defs: self.defs.clone(),
+                        funs: self.funs.clone(),
+                        num_assertion: self.assertions.len(),
+                    });
+                }
+            }
+            "pop" => {
+                let n = Self::level(cmd)?;
+                if n > self.levels.len() {
+                    return Err(ParseError::syntax(format!(
+                        "pop of {n} levels with {} pushed",
+                        self.levels.len()
+                    )));
+                }
+                let level = self.levels.drain(self.levels.len() - n..).next();
+                if let Some(level) = level {
+                    self.restore(level);
+                }
+            }
+            "reset" | "reset-assertions" => {
+                self.levels.clear();
+                self.vars.clear();
+                self.defs.clear();
+                self.funs.clear();
+                self.assertions.clear();
+            }
+            "declare-fun" => {
+                expect_len(cmd, 4)?;
+                if !cmd[2].list()?.is_empty() {
+                    return Err(ParseError::unsupported("uninterpreted function"));
+                }
+                self.declare(cmd[1].atom()?, &cmd[3])?;
+            }
+            "declare-const" => {
+                expect_len(cmd, 3)?;
+                self.declare(cmd[1].atom()?, &cmd[2])?;
+            }
+            "define-fun" => {
+                expect_len(cmd, 5)?;
+                let name = cmd[1].atom()?;
+                self.check_fresh(name)?;
+                let sort = self.sort(&cmd[3])?;
+                let params = cmd[2].list()?;
+                if params.is_empty() {
+                    let term = self.term(&cmd[4])?;
+                    if term.sort() != sort {
+                        return Err(ParseError::syntax(format!(
+                            "{name} of sort {sort:?} defined by a term of sort {:?}",
+                            term.sort()
+                        )));
+                    }
+                    self.defs.insert(name.to_string(), term);
+                } else {
+                    let mut ps = Vec::new();
+                    for p in params {
+                        let p = p.list()?;
+                        if p.len() != 2 {
+                            return Err(ParseError::syntax(format!(
+                                "invalid parameter {p:?} of {name}"
+                            )));
+                        }
+                        ps.push((p[0].atom()?.to_string(), self.sort(&p[1])?));
+                    }
+                    let def = FunDef {
+                        params: ps,
+                        sort,
+                        body: cmd[4].clone(),
+                    };
+                    self.funs.insert(name.to_string(), def);
+                }
+            }
+            "assert" => {
+                expect_len(cmd, 2)?;
+                let term = self.term(&cmd[1])?;
+                if !term.is_bool() {
+                    return Err(ParseError::syntax(format!(
+                        "assertion of sort {:?}",
+                        term.sort()
+                    )));
+                }
+                self.assertions.push(term);
+            }
+            c => return Err(ParseError::unsupported(format!("smtlib command {c}"))),
+        }
+        Ok(())
+    }
+
+    /// The number of levels of a `push` or `pop`, one if omitted.
+    fn level(cmd: &[Sexp]) -> Result<usize, ParseError> {
+        match cmd.len() {
+            1 => Ok(1),
+            2 => cmd[1].numeral(),
+            _ => expect_len(cmd, 2).map(|_| 0),
+        }
+    }
+
+    fn restore(&mut self, level: Level) {
+        self.vars = level.vars;
+        self.defs = level.defs;
+        self.funs = level.funs;
+        self.assertions.truncate(level.num_assertion);
+    }
+
+    fn check_fresh(&self, name: &str) -> Result<(), ParseError> {
+        if self.vars.contains_key(name)
+            || self.defs.contains_key(name)
+            || self.funs.contains_key(name)
+        {
+            return Err(ParseError::syntax(format!("redeclaration of {name}")));
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str, sort: &Sexp) -> Result<(), ParseError> {
+        self.check_fresh(name)?;
+        let sort = self.sort(sort)?;
+        let var = self.tm.new_named_var(name, sort);
+        self.vars.insert(name.to_string(), var);
+        Ok(())
+    }
+
+    fn sort(&self, sort: &Sexp) -> Result<Sort, ParseError> {
+        let unsupported = || ParseError::unsupported(format!("smtlib sort {sort:?}"));
+        match sort {
+            Sexp::Atom(a) if a == "Bool" => Ok(Sort::bool()),
+            Sexp::Atom(_) => Err(unsupported()),
+            Sexp::List(l) => match l.first().map(|h| h.atom()).transpose()? {
+                Some("_") if l.len() == 3 && l[1].atom()? == "BitVec" => match l[2].numeral()? {
+                    0 => Err(ParseError::syntax("bit-vector sort of width 0")),
+                    w => Ok(Sort::Bv(w)),
+                },
+                Some("Array") if l.len() == 3 => match (self.sort(&l[1])?, self.sort(&l[2])?) {
+                    (Sort::Bv(i), Sort::Bv(e)) => Ok(Sort::Array(i, e)),
+                    _ => Err(unsupported()),
+                },
+                _ => Err(unsupported()),
+            },
+        }
+    }
+
+    fn symbol(&mut self, name: &str) -> Result<Term, ParseError> {
+        if let Some(t) = self.scopes.iter().rev().find_map(|s| s.get(name)) {
+            return Ok(t.clone());
+        }
+        if let Some(t) = self.defs.get(name).or_else(|| self.vars.get(name)) {
+            return Ok(t.clone());
+        }
+        match name {
+            "true" => return Ok(self.tm.bool_const(true)),
+            "false" => return Ok(self.tm.bool_const(false)),
+            _ => (),
+        }
+        if let Some(b) = name.strip_prefix("#b")
+            && !b.is_empty()
+            && b.bytes().all(|c| c == b'0' || c == b'1')
+        {
+            return Ok(self.tm.bv_const(BvConst::from_binary(b, b.len())));
+        }
+        if let Some(x) = name.strip_prefix("#x")
+            && !x.is_empty()
+            && x.bytes().all(|c| c.is_ascii_hexdigit())
+        {
+            return Ok(self.tm.bv_const(BvConst::from_hex(x, x.len() * 4)));
+        }
+        Err(ParseError::syntax(format!("unknown smtlib symbol {name}")))
+    }
+
+    fn term(&mut self, e: &Sexp) -> Result<Term, ParseError> {
+        let l = match e {
+            Sexp::Atom(a) => return self.symbol(a),
+            Sexp::List(l) => l,
+        };
+        let Some(head) = l.first() else {
+            return Err(ParseError::syntax("empty term"));
+        };
+        match head {
+            Sexp::Atom(h) if h == "let" => {
+                expect_len(l, 3)?;
+                let mut scope = GHashMap::new();
+                for b in l[1].list()? {
+                    let b = b.list()?;
+                    if b.len() != 2 {
+                        return Err(ParseError::syntax(format!("invalid let binding {b:?}")));
+                    }
+                    scope.insert(b[0].atom()?.to_string(), self.term(&b[1])?);
+                }
+                self.scopes.push(scope);
+                let res = self.term(&l[2]);
+                self.scopes.pop();
+                res
+            }
+            Sexp::Atom(h) if h == "_" => {
+                expect_len(l, 3)?;
+                let value = l[1].atom()?;
+                let width = l[2].numeral()?;
+                match value.strip_prefix("bv") {
+                    Some(n)
+                        if !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit()) && width > 0 =>
+                    {
+                        Ok(self.tm.bv_const(BvConst::from_decimal(n, width)))
+                    }
+                    _ => Err(ParseError::syntax(format!(
+                        "invalid smtlib constant {value}"
+                    ))),
+                }
+            }
+            Sexp::Atom(h) if h == "!" => {
+                if l.len() < 2 {
+                    return Err(ParseError::syntax("annotation without term"));
+                }
+                let term = self.term(&l[1])?;
+                let mut attrs = l[2..].iter();
+                while let Some(attr) = attrs.next() {
+                    if attr.atom()? == ":named" {
+                        let Some(name) = attrs.next() else {
+                            return Err(ParseError::syntax(":named without name"));
+                        };
+                        let name = name.atom()?;
+                        self.check_fresh(name)?;
+                        self.defs.insert(name.to_string(), term.clone());
+                    }
+                }
+                Ok(term)
+            }
+            Sexp::Atom(h) => {
+                let args = l[1..]
+                    .iter()
+                    .map(|a| self.term(a))
+                    .collect::<Result<Vec<Term>, _>>()?;
+                if self.funs.contains_key(h) {
+                    self.expand(h, args)
+                } else {
+                    self.apply(h, &args)
+                }
+            }
+            Sexp::List(idx) => {
+                let args = l[1..]
+                    .iter()
+                    .map(|a| self.term(a))
+                    .collect::<Result<Vec<Term>, _>>()?;
+                self.apply_indexed(idx, &args)
+            }
+        }
+    }
+
+    fn expand(&mut self, name: &str, args: Vec<Term>) -> Result<Term, ParseError> {
+        if self.expanding.iter().any(|n| n == name) {
+            return Err(ParseError::syntax(format!(
+                "recursive definition of {name}"
+            )));
+        }
+        let fun = &self.funs[name];
+        if fun.params.len() != args.len()
+            || fun
+                .params
+                .iter()
+                .zip(&args)
+                .any(|((_, s), a)| a.sort() != *s)
+        {
+            return Err(ParseError::syntax(format!(
+                "ill-sorted application of {name}"
+            )));
+        }
+        let scope: GHashMap<String, Term> = fun
+            .params
+            .iter()
+            .map(|(p, _)| p.clone())
+            .zip(args)
+            .collect();
+        let (body, sort) = (fun.body.clone(), fun.sort);
+        let outer = std::mem::replace(&mut self.scopes, vec![scope]);
+        self.expanding.push(name.to_string());
+        let res = self.term(&body);
+        self.expanding.pop();
+        self.scopes = outer;
+        let res = res?;
+        if res.sort() != sort {
+            return Err(ParseError::syntax(format!(
+                "{name} of sort {sort:?} defined by a term of sort {:?}",
+                res.sort()
+            )));
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    fn fold(&mut self, name: &str, op: DynOp, args: &[Term]) -> Result<Term, ParseError> {
+        if args.is_empty() {
+            return Err(ParseError::syntax(format!("{name} without arguments")));
+        }
+        let mut acc = args[0].clone();
+        for x in &args[1..] {
+            let pair = [acc, x.clone()];
+            check_sorts(name, &op, &pair)?;
+            acc = self.tm.new_op_term(op.clone(), &pair);
+        }
+        Ok(acc)
+    }
+
+    fn apply(&mut self, name: &str, args: &[Term]) -> Result<Term, ParseError> {
+        match name {
+            "and" | "or" | "xor" | "bvand" | "bvor" | "bvxor" | "bvadd" | "bvmul" | "concat" => {
+                let op = smtlib_op(name).unwrap();
+                self.fold(name, op, args)
+            }
+            "=>" => {
+                let Some((last, rest)) = args.split_last() else {
+                    return Err(ParseError::syntax("=> without arguments"));
+                };
+                if args.iter().any(|a| !a.is_bool()) {
+                    return Err(ParseError::syntax("=> of non-boolean terms"));
+                }
+                Ok(rest.iter().rev().fold(last.clone(), |acc, x| !x | acc))
+            }
+            "=" | "distinct" => {
+                if args.len() < 2 {
+                    return Err(ParseError::syntax(format!(
+                        "{name} of fewer than two terms"
+                    )));
+                }
+                let eq: DynOp = Eq.into();
+                for w in args.windows(2) {
+                    check_sorts(name, &eq, w)?;
+                }
+                let mut res = Vec::new();
+                if name == "=" {
+                    res.extend(args.windows(2).map(|w| w[0].op1(Eq, &w[1])));
+                } else {
+                    for i in 0..args.len() {
+                        for j in i + 1..args.len() {
+                            res.push(args[i].op1(Neq, &args[j]));
+                        }
+                    }
+                }
+                self.fold(name, And.into(), &res)
+            }
+            _ => {
+                let op = smtlib_op(name)
+                    .ok_or_else(|| ParseError::unsupported(format!("smtlib function {name}")))?;
+                if op.num_operand() != args.len() {
+                    return Err(ParseError::syntax(format!(
+                        "{name} takes {} arguments, found {}",
+                        op.num_operand(),
+                        args.len()
+                    )));
+                }
+                check_sorts(name, &op, args)?;
+                Ok(self.tm.new_op_term(op, args))
+            }
+        }
+    }
+
+    fn apply_indexed(&mut self, idx: &[Sexp], args: &[Term]) -> Result<Term, ParseError> {
+        if idx.len() < 3 || idx[0].atom()? != "_" {
+            return Err(ParseError::syntax(format!(
+                "invalid indexed function {idx:?}"
+            )));
+        }
+        let name = idx[1].atom()?;
+        let index = |i: usize| -> Result<usize, ParseError> {
+            idx.get(i + 2)
+                .ok_or_else(|| ParseError::syntax(format!("missing index of {name}")))?
+                .numeral()
+        };
+        let x = match args {
+            [x] if !x.sort().is_array() => x,
+            _ => {
+                return Err(ParseError::syntax(format!(
+                    "{name} takes one bit-vector argument"
+                )));
+            }
+        };
+        match name {
+            "extract" => {
+                let (hi, lo) = (index(0)?, index(1)?);
+                if lo > hi || hi >= x.bv_len() {
+                    return Err(ParseError::syntax(format!(
+                        "extract {hi} {lo} of width {}",
+                        x.bv_len()
+                    )));
+                }
+                Ok(x.slice(hi, lo))
+            }
+            "zero_extend" => Ok(x.uext(index(0)?)),
+            "sign_extend" => Ok(x.sext(index(0)?)),
+            "repeat" => {
+                let copies = vec![x.clone(); index(0)?];
+                self.fold(name, Concat.into(), &copies)
+            }
+            "rotate_left" | "rotate_right" => {
+                let width = x.bv_len();
+                let mut k = index(0)? % width;
+                if name == "rotate_right" {
+                    k = (width - k) % width;
+                }
+                if k == 0 {
+                    return Ok(x.clone());
+                }
+                let low = x.slice(width - k - 1, 0);
+                let high = x.slice(width - 1, width - k);
+                Ok(low.op1(Concat, &high))
+            }
+            _ => Err(ParseError::unsupported(format!(
+                "smtlib indexed function {name}"
+            ))),
+        }
+    }
+}
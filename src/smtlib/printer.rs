@@ -0,0 +1,443 @@
+use crate::op::{
+    Add, And, Concat, DynOp, Eq, Ite, Mul, Not, Or, Read, Redxor, Rol, Ror, Saddo, Sdiv, Sdivo,
+    Sext, Slice, Sll, Slt, Smod, Smulo, Sra, Srem, Srl, Ssubo, Uaddo, Udiv, Ult, Umulo, Urem,
+    Usubo, Write, Xor,
+};
+use crate::{BvConst, OpTerm, PostOrder, Sort, Term, TermType, support_terms};
+use giputils::hash::{GHashMap, GHashSet};
+use lazy_static::lazy_static;
+use std::fmt::{self, Write as _};
+use std::ops::Deref;
+
+lazy_static! {
+    static ref SMTLIB_OP: GHashMap<DynOp, &'static str> = {
+        let ops: [(DynOp, &'static str); 28] = [
+            (Not.into(), "bvnot"),
+            (And.into(), "bvand"),
+            (Or.into(), "bvor"),
+            (Xor.into(), "bvxor"),
+            (Eq.into(), "="),
+            (Ult.into(), "bvult"),
+            (Slt.into(), "bvslt"),
+            (Sll.into(), "bvshl"),
+            (Srl.into(), "bvlshr"),
+            (Sra.into(), "bvashr"),
+            (Ite.into(), "ite"),
+            (Concat.into(), "concat"),
+            (Add.into(), "bvadd"),
+            (Mul.into(), "bvmul"),
+            (Udiv.into(), "bvudiv"),
+            (Urem.into(), "bvurem"),
+            (Sdiv.into(), "bvsdiv"),
+            (Srem.into(), "bvsrem"),
+            (Smod.into(), "bvsmod"),
+            (Read.into(), "select"),
+            (Write.into(), "store"),
+            (Uaddo.into(), "bvuaddo"),
+            (Saddo.into(), "bvsaddo"),
+            (Usubo.into(), "bvusubo"),
+            (Ssubo.into(), "bvssubo"),
+            (Umulo.into(), "bvumulo"),
+            (Smulo.into(), "bvsmulo"),
+            (Sdivo.into(), "bvsdivo"),
+        ];
+        ops.into_iter().collect()
+    };
+    /// Ops whose SMT-LIB result is `Bool`.
+    static ref PREDICATE: GHashSet<DynOp> = [
+        Eq.into(),
+        Ult.into(),
+        Slt.into(),
+        Uaddo.into(),
+        Saddo.into(),
+        Usubo.into(),
+        Ssubo.into(),
+        Umulo.into(),
+        Smulo.into(),
+        Sdivo.into(),
+    ]
+    .into_iter()
+    .collect();
+    /// Reserved words, usable as symbols only when quoted.
+    static ref RESERVED: GHashSet<&'static str> = [
+        "!", "_", "as", "BINARY", "DECIMAL", "exists", "HEXADECIMAL", "forall", "let", "match",
+        "NUMERAL", "par", "STRING",
+    ]
+    .into_iter()
+    .collect();
+    /// Functions of the logics we print, which a var must not redeclare.
+    static ref BUILTIN: GHashSet<&'static str> = [
+        "true", "false", "not", "=>", "and", "or", "xor", "=", "distinct", "ite", "concat",
+        "extract", "repeat", "zero_extend", "sign_extend", "rotate_left", "rotate_right", "bvnot",
+        "bvand", "bvor", "bvneg", "bvadd", "bvmul", "bvudiv", "bvurem", "bvshl", "bvlshr", "bvnand",
+        "bvnor", "bvxor", "bvxnor", "bvcomp", "bvsub", "bvsdiv", "bvsrem", "bvsmod", "bvashr",
+        "bvult", "bvule", "bvugt", "bvuge", "bvslt", "bvsle", "bvsgt", "bvsge", "bvnego", "select",
+        "store",
+    ]
+    .into_iter()
+    .chain(SMTLIB_OP.values().copied())
+    .collect();
+}
+
+#[inline]
+fn smtlib_symbol(op: &DynOp) -> &'static str {
+    SMTLIB_OP
+        .get(op)
+        .unwrap_or_else(|| panic!("{:?} not support smtlib", op))
+}
+
+/// The symbol of a var: its name, quoted if it is not a simple symbol or is a
+/// reserved word, or `v{id}` if it is unnamed, names a builtin function or
+/// cannot be quoted.
+pub fn var_to_smtlib(var: &Term) -> String {
+    let id = var.try_var_term().unwrap();
+    let Some(name) = var.var_name() else {
+        return format!("v{id}");
+    };
+    let simple = |c: char| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c);
+    if BUILTIN.contains(name.as_str()) {
+        format!("v{id}")
+    } else if !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(simple)
+        && !RESERVED.contains(name.as_str())
+    {
+        name
+    } else if name.contains(['|', '\\']) {
+        format!("v{id}")
+    } else {
+        format!("|{name}|")
+    }
+}
+
+pub fn sort_to_smtlib(sort: Sort) -> String {
+    match sort {
+        Sort::Bv(w) => format!("(_ BitVec {w})"),
+        Sort::Array(i, e) => format!("(Array (_ BitVec {i}) (_ BitVec {e}))"),
+    }
+}
+
+pub fn bv_const_to_smtlib(c: &BvConst) -> String {
+    format!("#b{c:b}")
+}
+
+/// Whether `op` is printed with its operands repeated, so that they are
+/// always bound: Redxor of more than one bit and rotations by a symbolic
+/// amount, while a constant amount is printed as a single `rotate_left` or
+/// `rotate_right`.
+#[inline]
+fn repeats_operands(op: &OpTerm) -> bool {
+    if op.op == Redxor {
+        op.terms[0].bv_len() > 1
+    } else if op.op == Rol || op.op == Ror {
+        op.terms[1].try_bv_const().is_none()
+    } else {
+        false
+    }
+}
+
+/// A piece of printed text, either literal or the text of a term.
+enum Piece {
+    Str(String),
+    Term(Term),
+}
+
+/// The text of an op term over the text of its operands, which is only
+/// pasted in when the term is flattened, so that printing stays linear in
+/// the DAG size however deep it is.
+#[derive(Default)]
+struct Doc(Vec<Piece>);
+
+impl Doc {
+    #[inline]
+    fn push_str(&mut self, s: &str) {
+        match self.0.last_mut() {
+            Some(Piece::Str(last)) => last.push_str(s),
+            _ => self.0.push(Piece::Str(s.to_string())),
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    #[inline]
+    fn term(&mut self, t: &Term) {
+        self.0.push(Piece::Term(t.clone()));
+    }
+}
+
+impl fmt::Write for Doc {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+/// Prints terms, binding every op term with more than one parent so that the
+/// output stays linear in the DAG size. The counts and bindings may be shared
+/// by several terms.
+#[derive(Default)]
+struct LetPrinter {
+    refs: GHashMap<Term, usize>,
+    names: GHashMap<Term, String>,
+    /// Docs of the unbound op terms not yet pasted into their parent.
+    docs: GHashMap<Term, Doc>,
+    /// Symbols of the vars and bindings.
+    used: GHashSet<String>,
+    bindings: Vec<(String, Term, String)>,
+}
+
+impl LetPrinter {
+    /// Gives every var of `vars` a distinct symbol, see [`var_to_smtlib`]. A
+    /// var whose symbol is taken by a previous var is printed as `v{id}`.
+    fn new(vars: &[Term]) -> Self {
+        let mut printer = Self::default();
+        for v in vars {
+            let mut symbol = var_to_smtlib(v);
+            if printer.used.contains(&symbol) {
+                symbol = format!("v{}", v.try_var_term().unwrap());
+            }
+            while printer.used.contains(&symbol) {
+                symbol.push('_');
+            }
+            printer.used.insert(symbol.clone());
+            printer.names.insert(v.clone(), symbol);
+        }
+        printer
+    }
+
+    /// Counts the parents of every op term below `term`, and `term` itself
+    /// once more as a root.
+    fn count(&mut self, term: &Term) {
+        let new: Vec<Term> = PostOrder::new([term], |t| self.refs.contains_key(t)).collect();
+        for t in new {
+            let TermType::Op(op) = t.deref() else {
+                continue;
+            };
+            let n = if repeats_operands(op) { 2 } else { 1 };
+            for s in op.terms.iter().filter(|s| s.try_op_term().is_some()) {
+                *self.refs.entry(s.clone()).or_insert(0) += n;
+            }
+            self.refs.entry(t.clone()).or_insert(0);
+        }
+        if term.try_op_term().is_some() {
+            *self.refs.get_mut(term).unwrap() += 1;
+        }
+    }
+
+    fn write_term(&mut self, term: &Term, out: &mut String) {
+        let order: Vec<Term> = PostOrder::new([term], |t| self.names.contains_key(t)).collect();
+        for t in order {
+            let TermType::Op(op) = t.deref() else {
+                continue;
+            };
+            let mut doc = Doc::default();
+            self.write_op(op, &mut doc);
+            if self.refs[&t] > 1 {
+                let mut expr = String::new();
+                self.flatten(doc, &mut expr);
+                let mut i = self.bindings.len();
+                while self.used.contains(&format!("_let_{i}")) {
+                    i += 1;
+                }
+                let name = format!("_let_{i}");
+                self.used.insert(name.clone());
+                self.bindings.push((name.clone(), t.clone(), expr));
+                self.names.insert(t, name);
+            } else {
+                self.docs.insert(t, doc);
+            }
+        }
+        let mut doc = Doc::default();
+        doc.term(term);
+        self.flatten(doc, out);
+    }
+
+    /// Appends the text of `doc` to `out`, pasting in the docs of the unbound
+    /// op terms it refers to, each of which has a single parent.
+    fn flatten(&mut self, doc: Doc, out: &mut String) {
+        let mut stack = vec![doc.0.into_iter()];
+        while let Some(pieces) = stack.last_mut() {
+            let Some(piece) = pieces.next() else {
+                stack.pop();
+                continue;
+            };
+            let term = match piece {
+                Piece::Str(s) => {
+                    out.push_str(&s);
+                    continue;
+                }
+                Piece::Term(t) => t,
+            };
+            if let Some(name) = self.names.get(&term) {
+                out.push_str(name);
+                continue;
+            }
+            match term.deref() {
+                TermType::Const(c) => out.push_str(&bv_const_to_smtlib(c)),
+                TermType::Var(_) => out.push_str(&var_to_smtlib(&term)),
+                TermType::Op(_) => stack.push(self.docs.remove(&term).unwrap().0.into_iter()),
+            }
+        }
+    }
+
+    fn write_op(&mut self, op: &OpTerm, out: &mut Doc) {
+        let t = &op.terms;
+        if PREDICATE.contains(&op.op) {
+            write!(out, "(ite ({} ", smtlib_symbol(&op.op)).unwrap();
+            out.term(&t[0]);
+            out.push(' ');
+            out.term(&t[1]);
+            out.push_str(") #b1 #b0)");
+        } else if op.op == Ite {
+            out.push_str("(ite (= ");
+            out.term(&t[0]);
+            out.push_str(" #b1) ");
+            out.term(&t[1]);
+            out.push(' ');
+            out.term(&t[2]);
+            out.push(')');
+        } else if let Some(Slice { hi, lo }) = op.op.downcast_ref::<Slice>() {
+            write!(out, "((_ extract {hi} {lo}) ").unwrap();
+            out.term(&t[0]);
+            out.push(')');
+        } else if let Some(Sext(ext)) = op.op.downcast_ref::<Sext>() {
+            write!(out, "((_ sign_extend {ext}) ").unwrap();
+            out.term(&t[0]);
+            out.push(')');
+        } else if op.op == Rol || op.op == Ror {
+            self.write_rotate(op, out);
+        } else if op.op == Redxor {
+            let width = t[0].bv_len();
+            for i in 0..width {
+                if i > 0 {
+                    out.push(' ');
+                }
+                if i + 1 < width {
+                    out.push_str("(bvxor ");
+                }
+                write!(out, "((_ extract {i} {i}) ").unwrap();
+                out.term(&t[0]);
+                out.push(')');
+            }
+            out.push_str(&")".repeat(width - 1));
+        } else {
+            write!(out, "({}", smtlib_symbol(&op.op)).unwrap();
+            for s in &op.terms {
+                out.push(' ');
+                out.term(s);
+            }
+            out.push(')');
+        }
+    }
+
+    /// Rotations by a constant are printed as `rotate_left`/`rotate_right`,
+    /// others as a disjunction of two shifts by the amount modulo the width.
+    fn write_rotate(&mut self, op: &OpTerm, out: &mut Doc) {
+        let t = &op.terms;
+        let width = t[0].bv_len();
+        let (dir, shl, shr) = if op.op == Rol {
+            ("left", "bvshl", "bvlshr")
+        } else {
+            ("right", "bvlshr", "bvshl")
+        };
+        if let Some(c) = t[1].try_bv_const() {
+            let k =
+                c.c.iter()
+                    .rev()
+                    .fold(0, |acc, b| (acc * 2 + *b as usize) % width);
+            write!(out, "((_ rotate_{dir} {k}) ").unwrap();
+            out.term(&t[0]);
+            out.push(')');
+            return;
+        }
+        // The amount is taken modulo the width in the wider of both sorts and
+        // then brought to the width of `x`.
+        let ylen = t[1].bv_len();
+        let len = width.max(ylen);
+        let w = bv_const_to_smtlib(&BvConst::from_usize(width, len));
+        let mut y = String::new();
+        let mut doc = Doc::default();
+        doc.term(&t[1]);
+        self.flatten(doc, &mut y);
+        if ylen < len {
+            y = format!("((_ zero_extend {}) {y})", len - ylen);
+        }
+        let mut amount = format!("(bvurem {y} {w})");
+        if width < len {
+            amount = format!("((_ extract {} 0) {amount})", width - 1);
+        }
+        let w = bv_const_to_smtlib(&BvConst::from_usize(width, width));
+        write!(out, "(bvor ({shl} ").unwrap();
+        out.term(&t[0]);
+        write!(out, " {amount}) ({shr} ").unwrap();
+        out.term(&t[0]);
+        write!(out, " (bvsub {w} {amount})))").unwrap();
+    }
+
+    /// Wraps `body` in a `let` per binding.
+    fn finish(self, body: String) -> String {
+        let mut res = String::new();
+        for (name, _, expr) in self.bindings.iter() {
+            write!(res, "(let (({name} {expr})) ").unwrap();
+        }
+        res.push_str(&body);
+        res.push_str(&")".repeat(self.bindings.len()));
+        res
+    }
+}
+
+/// Returns the SMT-LIB2 expression of `term`. Boolean results of `=`,
+/// comparisons and overflow predicates are converted to `(_ BitVec 1)`, as
+/// every term of sort `Bv(1)` is. Vars are printed as by [`dump_smtlib`].
+pub fn term_to_smtlib(term: &Term) -> String {
+    let mut printer = LetPrinter::new(&support_terms([term]));
+    printer.count(term);
+    let mut body = String::new();
+    printer.write_term(term, &mut body);
+    printer.finish(body)
+}
+
+/// Dumps an SMT-LIB2 script declaring every var of `roots` and asserting
+/// each of the boolean `roots`. Every var is declared with a distinct symbol,
+/// see [`var_to_smtlib`], and one taken by a previous var is declared as
+/// `v{id}`. Op terms shared within or between roots are
+/// defined once by `define-fun`.
+pub fn dump_smtlib(roots: &[Term]) -> String {
+    debug_assert!(roots.iter().all(|r| r.is_bool()));
+    let vars = support_terms(roots);
+    let logic = if vars.iter().any(|v| v.sort().is_array()) {
+        "QF_ABV"
+    } else {
+        "QF_BV"
+    };
+    let mut res = format!("(set-logic {logic})\n");
+    let mut printer = LetPrinter::new(&vars);
+    for v in vars.iter() {
+        let sort = sort_to_smtlib(v.sort());
+        writeln!(res, "(declare-fun {} () {sort})", printer.names[v]).unwrap();
+    }
+    for r in roots {
+        printer.count(r);
+    }
+    let asserts: Vec<String> = roots
+        .iter()
+        .map(|r| {
+            let mut body = String::new();
+            printer.write_term(r, &mut body);
+            body
+        })
+        .collect();
+    for (name, term, expr) in printer.bindings.iter() {
+        let sort = sort_to_smtlib(term.sort());
+        writeln!(res, "(define-fun {name} () {sort} {expr})").unwrap();
+    }
+    for body in asserts {
+        writeln!(res, "(assert (= {body} #b1))").unwrap();
+    }
+    res.push_str("(check-sat)\n");
+    res
+}
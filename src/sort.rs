@@ -35,6 +35,18 @@ impl Sort {
     pub fn is_array(&self) -> bool {
         matches!(self, Self::Array(_, _))
     }
+
+    /// Number of bits of a flattened value of this sort.
+    #[inline]
+    pub fn size(&self) -> usize {
+        match *self {
+            Sort::Bv(s) => s,
+            Sort::Array(i, e) => {
+                let shifted = 1usize.checked_shl(i as u32).unwrap();
+                shifted.checked_mul(e).unwrap()
+            }
+        }
+    }
 }
 
 impl Debug for Sort {
@@ -0,0 +1,72 @@
+use crate::{BvConst, OpTerm, PostOrder, Term, TermFold, TermVec, fold_terms};
+use giputils::hash::GHashMap;
+use std::sync::Arc;
+
+/// The vars of some terms sorted by id, found by a single traversal without
+/// keeping the support of the subterms.
+pub fn support_terms<'a>(terms: impl IntoIterator<Item = &'a Term>) -> TermVec {
+    let mut vars: TermVec = PostOrder::new(terms, |_| false)
+        .filter(|t| t.try_var_term().is_some())
+        .collect();
+    vars.sort_by_key(|v| v.try_var_term().unwrap());
+    vars
+}
+
+struct SupportFold;
+
+impl TermFold for SupportFold {
+    type Output = Arc<[Term]>;
+
+    #[inline]
+    fn fold_const(&mut self, _term: &Term, _c: &BvConst) -> Arc<[Term]> {
+        Arc::new([])
+    }
+
+    #[inline]
+    fn fold_var(&mut self, term: &Term, _v: u32) -> Arc<[Term]> {
+        Arc::new([term.clone()])
+    }
+
+    fn fold_op(&mut self, _term: &Term, _op: &OpTerm, terms: &[Arc<[Term]>]) -> Arc<[Term]> {
+        if let [t] = terms {
+            return t.clone();
+        }
+        let mut vars: Vec<Term> = terms.iter().flat_map(|t| t.iter().cloned()).collect();
+        vars.sort_by_key(|v| v.try_var_term().unwrap());
+        vars.dedup();
+        vars.into()
+    }
+}
+
+impl Term {
+    /// The vars `self` depends on sorted by id. `cache` keeps the support of
+    /// every subterm and may be shared between queries.
+    #[inline]
+    pub fn support(&self, cache: &mut GHashMap<Term, Arc<[Term]>>) -> Arc<[Term]> {
+        self.fold(&mut SupportFold, cache)
+    }
+
+    #[inline]
+    pub fn support_size(&self, cache: &mut GHashMap<Term, Arc<[Term]>>) -> usize {
+        self.support(cache).len()
+    }
+
+    #[inline]
+    pub fn depends_on(&self, var: &Term, cache: &mut GHashMap<Term, Arc<[Term]>>) -> bool {
+        let id = var.try_var_term().unwrap();
+        self.support(cache)
+            .binary_search_by_key(&id, |v| v.try_var_term().unwrap())
+            .is_ok()
+    }
+}
+
+impl TermVec {
+    /// The vars of all terms sorted by id, see [`Term::support`].
+    pub fn support(&self, cache: &mut GHashMap<Term, Arc<[Term]>>) -> TermVec {
+        fold_terms(self.iter(), &mut SupportFold, cache);
+        let mut vars: TermVec = self.iter().flat_map(|t| cache[t].iter().cloned()).collect();
+        vars.sort_by_key(|v| v.try_var_term().unwrap());
+        vars.dedup();
+        vars
+    }
+}
@@ -1,17 +1,19 @@
 use super::{op::DynOp, sort::Sort};
 use crate::TermVec;
-use crate::op::{Add, And, Ite, Neg, Not, Or, Sub, Xor};
-use giputils::grc::Grc;
+use crate::op::{Add, And, Ite, Neg, Not, Or, Sext, Slice, Sub, Uext, Xor};
+use crate::simplify::{RewriteCounters, RewriteStats};
 use giputils::hash::GHashMap;
 use std::fmt::{self, Debug};
-use std::ops::{ControlFlow, DerefMut, FromResidual, Index, Try};
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+use std::ops::{ControlFlow, FromResidual, Index, Try};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use std::{hash, ops};
 use std::{hash::Hash, ops::Deref};
 
 #[derive(Clone)]
 pub struct Term {
     tm: TermManager,
-    pub(crate) inner: Grc<TermInner>,
+    pub(crate) inner: Arc<TermInner>,
 }
 
 impl Term {
@@ -48,6 +50,15 @@ impl Term {
         }
     }
 
+    /// The name of a var, `None` for unnamed vars and non-var terms.
+    #[inline]
+    pub fn var_name(&self) -> Option<String> {
+        let id = self.try_var_term()?;
+        self.tm.inner.vars.read().unwrap().info[id as usize]
+            .name
+            .clone()
+    }
+
     #[inline]
     pub fn bv_len(&self) -> usize {
         self.sort().bv()
@@ -111,6 +122,21 @@ impl Term {
     pub fn ite(&self, t: &Term, e: &Term) -> Term {
         self.op2(Ite, t, e)
     }
+
+    #[inline]
+    pub fn slice(&self, hi: usize, lo: usize) -> Term {
+        self.op0(Slice { hi, lo })
+    }
+
+    #[inline]
+    pub fn sext(&self, ext: usize) -> Term {
+        self.op0(Sext(ext))
+    }
+
+    #[inline]
+    pub fn uext(&self, ext: usize) -> Term {
+        self.op0(Uext(ext))
+    }
 }
 
 impl Deref for Term {
@@ -125,14 +151,17 @@ impl Deref for Term {
 impl Hash for Term {
     #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
-        self.inner.hash(state);
+        Arc::as_ptr(&self.inner).hash(state);
     }
 }
 
 impl Debug for Term {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.inner.deref().fmt(f)
+        match self.var_name() {
+            Some(name) => write!(f, "{name}, {:?}", self.sort()),
+            None => self.inner.deref().fmt(f),
+        }
     }
 }
 
@@ -140,7 +169,7 @@ impl<T: AsRef<Term>> PartialEq<T> for Term {
     fn eq(&self, other: &T) -> bool {
         let other = other.as_ref();
         debug_assert!(self.tm == other.tm);
-        self.inner == other.inner
+        Arc::ptr_eq(&self.inner, &other.inner)
     }
 }
 
@@ -153,13 +182,6 @@ impl AsRef<Term> for Term {
     }
 }
 
-impl Drop for Term {
-    fn drop(&mut self) {
-        let g = self.clone();
-        self.tm.tgc.collect(g);
-    }
-}
-
 macro_rules! impl_unary_ops {
     ($trait:ident, $method:ident, $op:expr) => {
         impl std::ops::$trait for Term {
@@ -263,6 +285,82 @@ impl BvConst {
         Self { c: c.to_vec() }
     }
 
+    #[inline]
+    pub fn from_usize(mut v: usize, width: usize) -> Self {
+        let mut c = Vec::with_capacity(width);
+        while c.len() < width {
+            c.push(v & 1 == 1);
+            v >>= 1;
+        }
+        Self { c }
+    }
+
+    /// Parses a binary numeral, most significant digit first, zero extended
+    /// or truncated to `width`.
+    pub fn from_binary(s: &str, width: usize) -> Self {
+        let mut c: Vec<bool> = s
+            .bytes()
+            .rev()
+            .map(|b| match b {
+                b'0' => false,
+                b'1' => true,
+                _ => panic!("invalid binary numeral {s}"),
+            })
+            .collect();
+        c.resize(width, false);
+        Self { c }
+    }
+
+    /// Parses a hexadecimal numeral, zero extended or truncated to `width`.
+    pub fn from_hex(s: &str, width: usize) -> Self {
+        let mut c = Vec::with_capacity(s.len() * 4);
+        for h in s.chars().rev() {
+            let d = h
+                .to_digit(16)
+                .unwrap_or_else(|| panic!("invalid hex numeral {s}"));
+            c.extend((0..4).map(|i| d >> i & 1 == 1));
+        }
+        c.resize(width, false);
+        Self { c }
+    }
+
+    /// Parses a decimal numeral, a leading `-` gives the two's complement.
+    pub fn from_decimal(s: &str, width: usize) -> Self {
+        let (neg, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let mut digits: Vec<u8> = s
+            .bytes()
+            .map(|d| {
+                assert!(d.is_ascii_digit(), "invalid decimal numeral {s}");
+                d - b'0'
+            })
+            .collect();
+        let mut c = Vec::with_capacity(width);
+        while c.len() < width {
+            c.push(digits.last().is_some_and(|d| d % 2 == 1));
+            let mut rem = 0;
+            for d in digits.iter_mut() {
+                let cur = rem * 10 + *d;
+                *d = cur / 2;
+                rem = cur % 2;
+            }
+        }
+        if neg {
+            for b in c.iter_mut() {
+                *b = !*b;
+            }
+            for b in c.iter_mut() {
+                *b = !*b;
+                if *b {
+                    break;
+                }
+            }
+        }
+        Self { c }
+    }
+
     #[inline]
     pub fn is_zero(&self) -> bool {
         self.c.iter().all(|x| !x)
@@ -273,6 +371,19 @@ impl BvConst {
         self.c.iter().all(|x| *x)
     }
 
+    #[inline]
+    pub fn is_one(&self) -> bool {
+        self.c[0] && self.c[1..].iter().all(|x| !x)
+    }
+
+    /// Returns `k` if the constant is exactly `2^k`.
+    #[inline]
+    pub fn power_of_two(&self) -> Option<usize> {
+        let mut ones = self.c.iter().enumerate().filter(|(_, b)| **b);
+        let (k, _) = ones.next()?;
+        ones.next().is_none().then_some(k)
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.c.len()
@@ -288,6 +399,13 @@ impl BvConst {
 }
 
 impl Debug for BvConst {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BvConst({:b})", self)
+    }
+}
+
+impl fmt::Binary for BvConst {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let c: String = self
@@ -296,7 +414,16 @@ impl Debug for BvConst {
             .map(|b| if *b { '1' } else { '0' })
             .rev()
             .collect();
-        write!(f, "BvConst({:})", c)
+        f.write_str(&c)
+    }
+}
+
+impl Index<usize> for BvConst {
+    type Output = bool;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.c[index]
     }
 }
 
@@ -373,49 +500,103 @@ impl Try for TermResult {
     }
 }
 
-#[derive(Clone, Default, PartialEq, Eq, Debug)]
-pub struct TermGC {
-    garbage: Grc<Vec<Term>>,
+/// Metadata attached to a var.
+#[derive(Clone, Debug, Default)]
+pub struct VarInfo {
+    name: Option<String>,
+    /// The time frame of a var created by unrolling.
+    pub frame: Option<usize>,
+    /// The var a frame copy was created from.
+    pub origin: Option<u32>,
+    pub attrs: GHashMap<String, String>,
 }
 
-impl TermGC {
+impl VarInfo {
     #[inline]
-    pub fn collect(&mut self, term: Term) {
-        self.garbage.push(term);
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
     }
 }
 
+/// The unique table is split into shards, each behind its own lock, so that
+/// threads sharing a manager rarely contend.
+const NUM_SHARD: usize = 64;
+
 #[derive(Default)]
-pub struct TermManagerInner {
-    tgc: TermGC,
-    num_var: u32,
-    map: GHashMap<TermType, Term>,
+struct VarTable {
+    info: Vec<VarInfo>,
+    names: GHashMap<String, u32>,
+}
+
+impl VarTable {
+    #[inline]
+    fn unindex_name(&mut self, id: u32) {
+        if let Some(name) = self.info[id as usize].name.take()
+            && self.names.get(&name) == Some(&id)
+        {
+            self.names.remove(&name);
+        }
+    }
 }
 
-#[derive(Clone, Default, PartialEq, Eq)]
+struct TermManagerInner {
+    shards: [Mutex<GHashMap<TermType, Term>>; NUM_SHARD],
+    hasher: BuildHasherDefault<DefaultHasher>,
+    vars: RwLock<VarTable>,
+    rewrite_stats: RewriteCounters,
+}
+
+impl Default for TermManagerInner {
+    fn default() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| Mutex::default()),
+            hasher: Default::default(),
+            vars: Default::default(),
+            rewrite_stats: Default::default(),
+        }
+    }
+}
+
+/// A handle to a term table shared by its clones, which may be used from
+/// different threads to build and hash-cons terms concurrently.
+#[derive(Clone, Default)]
 pub struct TermManager {
-    inner: Grc<TermManagerInner>,
+    inner: Arc<TermManagerInner>,
 }
 
+// Terms and their manager must stay shareable between threads.
+const _: fn() = || {
+    fn f<T: Send + Sync>() {}
+    f::<Term>();
+    f::<TermManager>();
+};
+
 impl TermManager {
     #[inline]
     pub fn new() -> Self {
         Self::default()
     }
 
+    #[inline]
+    fn shard(&self, ty: &TermType) -> MutexGuard<'_, GHashMap<TermType, Term>> {
+        let i = self.inner.hasher.hash_one(ty) as usize % NUM_SHARD;
+        self.inner.shards[i].lock().unwrap()
+    }
+
     #[inline]
     fn new_term(&mut self, ty: TermType, sort: Sort) -> Term {
-        match self.map.get(&ty) {
+        let mut shard = self.shard(&ty);
+        match shard.get(&ty) {
             Some(term) => term.clone(),
             None => {
                 let term = Term {
                     tm: self.clone(),
-                    inner: Grc::new(TermInner {
+                    inner: Arc::new(TermInner {
                         sort,
                         ty: ty.clone(),
                     }),
                 };
-                self.map.insert(ty, term.clone());
+                shard.insert(ty, term.clone());
                 term
             }
         }
@@ -457,17 +638,19 @@ impl TermManager {
     }
 
     #[inline]
-    pub fn bv_const_from_usize(&mut self, mut v: usize, width: usize) -> Term {
-        let mut bv = Vec::new();
-        while v > 0 {
-            bv.push(width & 1 == 1);
-            v = v >> 1;
-        }
-        while bv.len() < width {
-            bv.push(false);
-        }
-        bv.truncate(width);
-        self.bv_const(BvConst::new(&bv))
+    pub fn bv_const_from_usize(&mut self, v: usize, width: usize) -> Term {
+        self.bv_const(BvConst::from_usize(v, width))
+    }
+
+    /// Folds `op` applied to `terms` into a constant if every operand is a
+    /// constant.
+    #[inline]
+    pub fn try_eval_const(&mut self, op: &DynOp, terms: &[Term]) -> Option<Term> {
+        let consts: Vec<BvConst> = terms
+            .iter()
+            .map(|t| t.try_bv_const().cloned())
+            .collect::<Option<_>>()?;
+        Some(self.bv_const(op.eval(&consts)))
     }
 
     #[inline]
@@ -481,6 +664,9 @@ impl TermManager {
         if !op.is_core() {
             return op.normalize(self, &terms);
         }
+        if let Some(c) = self.try_eval_const(&op, &terms) {
+            return c;
+        }
         let sort = op.sort(&terms);
         let term = TermType::Op(OpTerm::new(op, terms));
         self.new_term(term, sort)
@@ -512,37 +698,134 @@ impl TermManager {
 
     #[inline]
     pub fn new_var(&mut self, sort: Sort) -> Term {
-        let id = self.num_var;
-        self.num_var += 1;
+        let id = {
+            let mut vars = self.inner.vars.write().unwrap();
+            vars.info.push(VarInfo::default());
+            vars.info.len() as u32 - 1
+        };
         let term = TermType::Var(id);
         self.new_term(term, sort)
     }
 
     #[inline]
-    pub fn garbage_collect(&mut self) {}
+    pub fn new_named_var(&mut self, name: impl Into<String>, sort: Sort) -> Term {
+        let var = self.new_var(sort);
+        self.set_var_name(&var, name);
+        var
+    }
+
+    /// Names `var`, replacing its previous name.
+    pub fn set_var_name(&mut self, var: &Term, name: impl Into<String>) {
+        let id = var.try_var_term().unwrap();
+        let name = name.into();
+        let mut vars = self.inner.vars.write().unwrap();
+        vars.unindex_name(id);
+        vars.names.insert(name.clone(), id);
+        vars.info[id as usize].name = Some(name);
+    }
+
+    /// The var most recently given `name`.
+    #[inline]
+    pub fn var_by_name(&self, name: &str) -> Option<Term> {
+        let id = *self.inner.vars.read().unwrap().names.get(name)?;
+        let ty = TermType::Var(id);
+        self.shard(&ty).get(&ty).cloned()
+    }
+
+    #[inline]
+    pub fn var_info(&self, var: &Term) -> VarInfo {
+        let id = var.try_var_term().unwrap();
+        self.inner.vars.read().unwrap().info[id as usize].clone()
+    }
+
+    /// Updates the frame, origin and attributes of `var`.
+    #[inline]
+    pub fn update_var_info<R>(&mut self, var: &Term, f: impl FnOnce(&mut VarInfo) -> R) -> R {
+        let id = var.try_var_term().unwrap();
+        f(&mut self.inner.vars.write().unwrap().info[id as usize])
+    }
+
+    /// Removes every term referenced only by the unique table or by other
+    /// removed terms, returning the number of removed terms. The table is
+    /// locked meanwhile, so no other thread can revive a dead term.
+    pub fn garbage_collect(&mut self) -> usize {
+        let mut shards: Vec<_> = self
+            .inner
+            .shards
+            .iter()
+            .map(|s| s.lock().unwrap())
+            .collect();
+        // A dead term is held by the table and by the worklist.
+        let is_dead = |t: &Term| Arc::strong_count(&t.inner) == 2;
+        let mut worklist: Vec<Term> = shards
+            .iter()
+            .flat_map(|s| s.values())
+            .filter(|t| Arc::strong_count(&t.inner) == 1)
+            .cloned()
+            .collect();
+        let mut num_free = 0;
+        while let Some(term) = worklist.pop() {
+            debug_assert!(is_dead(&term));
+            let i = self.inner.hasher.hash_one(term.deref()) as usize % NUM_SHARD;
+            shards[i].remove(term.deref());
+            num_free += 1;
+            let mut terms = match term.deref() {
+                TermType::Op(op) => op.terms.clone(),
+                TermType::Var(id) => {
+                    let mut vars = self.inner.vars.write().unwrap();
+                    vars.unindex_name(*id);
+                    vars.info[*id as usize] = VarInfo::default();
+                    continue;
+                }
+                _ => continue,
+            };
+            drop(term);
+            // Each copy of a repeated operand, e.g. of `x + x`, holds a
+            // reference, so keep one to see a dead child by its count alone
+            // rather than by the order the copies are dropped in.
+            terms.sort_by_key(|t| Arc::as_ptr(&t.inner));
+            terms.dedup();
+            worklist.extend(terms.into_iter().filter(is_dead));
+        }
+        num_free
+    }
 
     #[inline]
     pub fn size(&self) -> usize {
-        self.map.len()
+        self.inner
+            .shards
+            .iter()
+            .map(|s| s.lock().unwrap().len())
+            .sum()
+    }
+
+    /// Counts one application of the simplification rule `rule`.
+    #[inline]
+    pub(crate) fn record_rewrite(&self, rule: &'static str) {
+        self.inner.rewrite_stats.record(rule);
     }
-}
 
-impl Deref for TermManager {
-    type Target = TermManagerInner;
+    /// Number of applications of each simplification rule so far.
+    #[inline]
+    pub fn rewrite_stats(&self) -> RewriteStats {
+        self.inner.rewrite_stats.stats()
+    }
 
     #[inline]
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    pub fn reset_rewrite_stats(&mut self) {
+        self.inner.rewrite_stats.reset();
     }
 }
 
-impl DerefMut for TermManager {
+impl PartialEq for TermManager {
     #[inline]
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
     }
 }
 
+impl Eq for TermManager {}
+
 impl Debug for TermManager {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -0,0 +1,32 @@
+use crate::{Term, TermVec};
+use giputils::hash::GHashMap;
+
+/// A word-level transition system. Latches without an entry in `init` are
+/// uninitialized.
+#[derive(Clone, Default, Debug)]
+pub struct TransitionSys {
+    pub input: TermVec,
+    pub latch: TermVec,
+    pub init: GHashMap<Term, Term>,
+    pub next: GHashMap<Term, Term>,
+    pub bad: TermVec,
+    pub constraint: TermVec,
+    pub output: TermVec,
+}
+
+impl TransitionSys {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn add_latch(&mut self, latch: Term, init: Option<Term>, next: Term) {
+        debug_assert!(latch.try_var_term().is_some());
+        if let Some(init) = init {
+            self.init.insert(latch.clone(), init);
+        }
+        self.next.insert(latch.clone(), next);
+        self.latch.push(latch);
+    }
+}
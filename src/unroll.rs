@@ -0,0 +1,122 @@
+use crate::op::{Eq, Or};
+use crate::{OpTerm, Term, TermManager, TermMap, TermVec, TransitionSys};
+use giputils::hash::GHashMap;
+
+/// Unrolls a transition system into time frames. Every var of the system,
+/// including free vars of its terms, gets a fresh var in each frame, named
+/// `name@k` after a named var and recording its frame and origin.
+pub struct TransysUnroll {
+    tm: TermManager,
+    pub ts: TransitionSys,
+    /// Frame versions of the terms met so far, one map per frame.
+    frames: Vec<GHashMap<Term, Term>>,
+}
+
+struct FrameMap<'a> {
+    tm: &'a mut TermManager,
+    k: usize,
+}
+
+impl TermMap for FrameMap<'_> {
+    fn map_var(&mut self, term: &Term, v: u32) -> Term {
+        let var = self.tm.new_var(term.sort());
+        if let Some(name) = term.var_name() {
+            self.tm.set_var_name(&var, format!("{name}@{}", self.k));
+        }
+        self.tm.update_var_info(&var, |info| {
+            info.frame = Some(self.k);
+            info.origin = Some(v);
+        });
+        var
+    }
+
+    #[inline]
+    fn map_op(&mut self, _term: &Term, op: &OpTerm, terms: &[Term]) -> Term {
+        self.tm.new_op_term(op.op.clone(), terms)
+    }
+}
+
+impl TransysUnroll {
+    #[inline]
+    pub fn new(tm: &TermManager, ts: &TransitionSys) -> Self {
+        Self {
+            tm: tm.clone(),
+            ts: ts.clone(),
+            frames: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn num_frame(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the version of `term` at frame `k`, unrolling up to `k` if
+    /// needed.
+    pub fn term(&mut self, term: &Term, k: usize) -> Term {
+        while self.frames.len() <= k {
+            self.frames.push(GHashMap::new());
+        }
+        let mut frame = FrameMap {
+            tm: &mut self.tm,
+            k,
+        };
+        term.map(&mut frame, &mut self.frames[k])
+    }
+
+    #[inline]
+    pub fn terms<'a>(&mut self, terms: impl IntoIterator<Item = &'a Term>, k: usize) -> TermVec {
+        terms.into_iter().map(|t| self.term(t, k)).collect()
+    }
+
+    /// Equalities between the latches of frame 0 and their init values.
+    pub fn init(&mut self) -> TermVec {
+        let init: Vec<(Term, Term)> = self
+            .ts
+            .latch
+            .iter()
+            .filter_map(|l| self.ts.init.get(l).map(|i| (l.clone(), i.clone())))
+            .collect();
+        init.iter()
+            .map(|(l, i)| {
+                let l = self.term(l, 0);
+                let i = self.term(i, 0);
+                self.tm.new_op_term(Eq, [&l, &i])
+            })
+            .collect()
+    }
+
+    /// Equalities between the latches of frame `k + 1` and their next values
+    /// at frame `k`.
+    pub fn trans(&mut self, k: usize) -> TermVec {
+        let next: Vec<(Term, Term)> = self
+            .ts
+            .latch
+            .iter()
+            .filter_map(|l| self.ts.next.get(l).map(|n| (l.clone(), n.clone())))
+            .collect();
+        next.iter()
+            .map(|(l, n)| {
+                let l = self.term(l, k + 1);
+                let n = self.term(n, k);
+                self.tm.new_op_term(Eq, [&l, &n])
+            })
+            .collect()
+    }
+
+    #[inline]
+    pub fn constraint(&mut self, k: usize) -> TermVec {
+        let constraint = self.ts.constraint.clone();
+        self.terms(constraint.iter(), k)
+    }
+
+    /// The disjunction of the bad properties at frame `k`.
+    pub fn bad(&mut self, k: usize) -> Term {
+        let bad = self.ts.bad.clone();
+        let bad = self.terms(bad.iter(), k);
+        if bad.is_empty() {
+            return self.tm.bool_const(false);
+        }
+        self.tm.new_op_terms_fold(Or, bad.iter())
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{BvConst, OpTerm, Term, TermType};
+use giputils::hash::{GHashMap, GHashSet};
+use std::ops::Deref;
+
+/// Iterates over the distinct subterms of some roots in post-order, each
+/// term after its operands, with an explicit stack instead of recursion.
+/// Terms for which `prune` holds are neither visited nor descended into.
+pub struct PostOrder<P: FnMut(&Term) -> bool> {
+    stack: Vec<(Term, bool)>,
+    visited: GHashSet<Term>,
+    prune: P,
+}
+
+impl<P: FnMut(&Term) -> bool> PostOrder<P> {
+    #[inline]
+    pub fn new<'a>(roots: impl IntoIterator<Item = &'a Term>, prune: P) -> Self {
+        let mut stack: Vec<(Term, bool)> = roots.into_iter().map(|r| (r.clone(), false)).collect();
+        stack.reverse();
+        Self {
+            stack,
+            visited: GHashSet::new(),
+            prune,
+        }
+    }
+}
+
+impl<P: FnMut(&Term) -> bool> Iterator for PostOrder<P> {
+    type Item = Term;
+
+    fn next(&mut self) -> Option<Term> {
+        while let Some((term, expanded)) = self.stack.pop() {
+            if expanded {
+                return Some(term);
+            }
+            if self.visited.contains(&term) || (self.prune)(&term) {
+                continue;
+            }
+            self.visited.insert(term.clone());
+            self.stack.push((term.clone(), true));
+            if let TermType::Op(op) = term.deref() {
+                for t in op.terms.iter().rev() {
+                    if !self.visited.contains(t) {
+                        self.stack.push((t.clone(), false));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Term {
+    /// The distinct subterms of `self` in post-order, see [`PostOrder`].
+    #[inline]
+    pub fn post_order(&self) -> PostOrder<impl FnMut(&Term) -> bool> {
+        PostOrder::new([self], |_| false)
+    }
+}
+
+/// A bottom-up computation over a term DAG, evaluated once per distinct
+/// term by [`Term::fold`].
+pub trait TermFold {
+    type Output: Clone;
+
+    /// The result of `term` if known without visiting its operands.
+    #[inline]
+    fn lookup(&mut self, _term: &Term) -> Option<Self::Output> {
+        None
+    }
+
+    fn fold_const(&mut self, term: &Term, c: &BvConst) -> Self::Output;
+
+    fn fold_var(&mut self, term: &Term, v: u32) -> Self::Output;
+
+    fn fold_op(&mut self, term: &Term, op: &OpTerm, terms: &[Self::Output]) -> Self::Output;
+}
+
+/// Folds every root into `cache`, which holds the results of the terms
+/// already folded and may be shared between calls with the same `f`.
+pub fn fold_terms<'a, F: TermFold + ?Sized>(
+    roots: impl IntoIterator<Item = &'a Term>,
+    f: &mut F,
+    cache: &mut GHashMap<Term, F::Output>,
+) {
+    let order: Vec<Term> = PostOrder::new(roots, |t| {
+        if cache.contains_key(t) {
+            return true;
+        }
+        match f.lookup(t) {
+            Some(res) => {
+                cache.insert(t.clone(), res);
+                true
+            }
+            None => false,
+        }
+    })
+    .collect();
+    for term in order {
+        let res = match term.deref() {
+            TermType::Const(c) => f.fold_const(&term, c),
+            TermType::Var(v) => f.fold_var(&term, *v),
+            TermType::Op(op) => {
+                let terms: Vec<F::Output> = op.terms.iter().map(|t| cache[t].clone()).collect();
+                f.fold_op(&term, op, &terms)
+            }
+        };
+        cache.insert(term, res);
+    }
+}
+
+/// A [`TermFold`] rebuilding terms, by default into themselves.
+pub trait TermMap {
+    /// The image of `term` if known without visiting its operands.
+    #[inline]
+    fn lookup(&mut self, _term: &Term) -> Option<Term> {
+        None
+    }
+
+    #[inline]
+    fn map_const(&mut self, term: &Term, _c: &BvConst) -> Term {
+        term.clone()
+    }
+
+    #[inline]
+    fn map_var(&mut self, term: &Term, _v: u32) -> Term {
+        term.clone()
+    }
+
+    /// Rebuilds `term` over the images of its operands.
+    #[inline]
+    fn map_op(&mut self, term: &Term, op: &OpTerm, terms: &[Term]) -> Term {
+        if op.terms == terms {
+            term.clone()
+        } else {
+            term.get_manager().new_op_term(op.op.clone(), terms)
+        }
+    }
+}
+
+struct MapFold<'a, M: ?Sized>(&'a mut M);
+
+impl<M: TermMap + ?Sized> TermFold for MapFold<'_, M> {
+    type Output = Term;
+
+    #[inline]
+    fn lookup(&mut self, term: &Term) -> Option<Term> {
+        self.0.lookup(term)
+    }
+
+    #[inline]
+    fn fold_const(&mut self, term: &Term, c: &BvConst) -> Term {
+        self.0.map_const(term, c)
+    }
+
+    #[inline]
+    fn fold_var(&mut self, term: &Term, v: u32) -> Term {
+        self.0.map_var(term, v)
+    }
+
+    #[inline]
+    fn fold_op(&mut self, term: &Term, op: &OpTerm, terms: &[Term]) -> Term {
+        self.0.map_op(term, op, terms)
+    }
+}
+
+/// Maps every root into `cache` like [`fold_terms`].
+#[inline]
+pub fn map_terms<'a, M: TermMap + ?Sized>(
+    roots: impl IntoIterator<Item = &'a Term>,
+    m: &mut M,
+    cache: &mut GHashMap<Term, Term>,
+) {
+    fold_terms(roots, &mut MapFold(m), cache)
+}
+
+impl Term {
+    /// Folds `self` bottom-up without recursion, see [`fold_terms`].
+    #[inline]
+    pub fn fold<F: TermFold + ?Sized>(
+        &self,
+        f: &mut F,
+        cache: &mut GHashMap<Term, F::Output>,
+    ) -> F::Output {
+        fold_terms([self], f, cache);
+        cache[self].clone()
+    }
+
+    /// Maps `self` bottom-up without recursion, see [`fold_terms`].
+    #[inline]
+    pub fn map<M: TermMap + ?Sized>(&self, m: &mut M, cache: &mut GHashMap<Term, Term>) -> Term {
+        self.fold(&mut MapFold(m), cache)
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::assert_equivalent;
+use fol::aiger::{dump_aag, dump_aig, parse_aiger};
+use fol::op::*;
+use fol::*;
+
+/// The inits, nexts, outputs, bads and constraints of `ts` in a fixed order.
+fn roots(ts: &TransitionSys) -> Vec<Term> {
+    let mut roots: Vec<Term> = ts
+        .latch
+        .iter()
+        .filter_map(|l| ts.init.get(l))
+        .cloned()
+        .collect();
+    roots.extend(ts.latch.iter().map(|l| ts.next[l].clone()));
+    roots.extend(ts.output.iter().cloned());
+    roots.extend(ts.bad.iter().cloned());
+    roots.extend(ts.constraint.iter().cloned());
+    roots
+}
+
+#[test]
+fn dump_parse_roundtrip() {
+    let mut tm = TermManager::new();
+    let mut ts = TransitionSys::new();
+    let i = tm.new_named_var("i", Sort::Bv(2));
+    let j = tm.new_named_var("j", Sort::Bv(1));
+    ts.input.extend([i.clone(), j.clone()]);
+    let l = tm.new_named_var("l", Sort::Bv(2));
+    let m = tm.new_named_var("m", Sort::Bv(1));
+    let s = &l + &i;
+    let one = tm.bv_const_one(2);
+    ts.add_latch(l.clone(), Some(one), tm.new_op_term(Ite, [&j, &s, &l]));
+    ts.add_latch(m.clone(), None, tm.new_op_term(Ult, [&l, &i]));
+    ts.output.push(tm.new_op_term(Mul, [&s, &i]));
+    let shl = tm.new_op_term(Sll, [&l, &i]);
+    ts.bad.push(tm.new_op_term(Eq, [&s, &shl]));
+    ts.bad.push(&m ^ &tm.new_op_term(Slt, [&i, &l]));
+    ts.constraint.push(!&(&j & &m));
+    let ts = ts.bitblast(&mut tm);
+    let vars: Vec<Term> = ts.input.iter().chain(ts.latch.iter()).cloned().collect();
+
+    let aag = dump_aag(&ts);
+    let aig = dump_aig(&ts);
+    for data in [aag.as_bytes(), &aig] {
+        let mut tm2 = TermManager::new();
+        let parsed = parse_aiger(&mut tm2, data).unwrap();
+        assert_eq!(parsed.input.len(), ts.input.len());
+        assert_eq!(parsed.latch.len(), ts.latch.len());
+        let parsed_vars: Vec<Term> = parsed
+            .input
+            .iter()
+            .chain(parsed.latch.iter())
+            .cloned()
+            .collect();
+        assert_equivalent(&roots(&ts), &vars, &roots(&parsed), &parsed_vars);
+    }
+}
+
+#[test]
+fn unordered_shared_gates() {
+    // a = b & c, c = b & d, d = x & !y, b = x & y, listed before use.
+    let aag = "aag 6 2 0 1 4\n2\n4\n12\n12 6 10\n10 6 8\n8 2 5\n6 2 4\n";
+    let mut tm = TermManager::new();
+    let ts = parse_aiger(&mut tm, aag.as_bytes()).unwrap();
+    let (x, y) = (&ts.input[0], &ts.input[1]);
+    let b = x & y;
+    let expected = &b & &(&b & &(x & &!y));
+    assert_equivalent(&ts.output, &ts.input, &[expected], &ts.input);
+}
+
+#[test]
+fn parse_errors() {
+    let mut tm = TermManager::new();
+    for data in [
+        &b"aag 3 1 0 1 2\n2\n4\n4 6 2\n6 4 2\n"[..],
+        b"aag 2 1 0 1 1\n2\n4\n4 8 2\n",
+        b"aag 1 1 0 1\n2\n",
+        b"aig 2 1 0 1 1\n4\n",
+        b"aig 2 1 0 1 1\n4\n\x82",
+        b"aag 1 1 0 0 0 0 0 1\n2\n",
+        b"",
+    ] {
+        assert!(parse_aiger(&mut tm, data).is_err(), "{data:?}");
+    }
+}
@@ -0,0 +1,64 @@
+use fol::op::*;
+use fol::*;
+use giputils::hash::GHashMap;
+
+/// Checks the bitblasted `t` against `Model::eval` under every assignment
+/// of `vars`, bitblasting each var into constant bits.
+fn check(tm: &mut TermManager, t: &Term, vars: &[Term]) {
+    let num_bits: usize = vars.iter().map(|v| v.sort().size()).sum();
+    for a in 0..1usize << num_bits {
+        let mut map = GHashMap::new();
+        let mut model = Model::new();
+        let mut off = 0;
+        for v in vars {
+            let bits: Vec<bool> = (0..v.sort().size())
+                .map(|i| a >> (off + i) & 1 == 1)
+                .collect();
+            off += bits.len();
+            let consts = bits.iter().map(|b| tm.bool_const(*b)).collect();
+            map.insert(v.clone(), consts);
+            model.insert(v.clone(), BvConst::new(&bits));
+        }
+        let got: Vec<bool> = t
+            .bitblast(tm, &mut map)
+            .iter()
+            .map(|b| b.try_bv_const().unwrap().bool().unwrap())
+            .collect();
+        let exp = model.eval(t, &mut GHashMap::new());
+        assert_eq!(BvConst::new(&got), exp, "{t:?} under {a}");
+    }
+}
+
+#[test]
+fn shifts() {
+    let mut tm = TermManager::new();
+    for w in 1..=5 {
+        let x = tm.new_var(Sort::Bv(w));
+        let y = tm.new_var(Sort::Bv(w));
+        for op in [
+            DynOp::from(Sll),
+            Srl.into(),
+            Sra.into(),
+            Rol.into(),
+            Ror.into(),
+        ] {
+            let t = tm.new_op_term(op, [&x, &y]);
+            check(&mut tm, &t, &[x.clone(), y.clone()]);
+        }
+    }
+}
+
+#[test]
+fn array_read_write() {
+    let mut tm = TermManager::new();
+    for (iw, ew) in [(1, 2), (2, 1), (2, 2), (3, 1)] {
+        let a = tm.new_var(Sort::Array(iw, ew));
+        let i = tm.new_var(Sort::Bv(iw));
+        let e = tm.new_var(Sort::Bv(ew));
+        let vars = [a.clone(), i.clone(), e.clone()];
+        let t = tm.new_op_term(Read, [&a, &i]);
+        check(&mut tm, &t, &vars);
+        let w = tm.new_op_term(Write, [&a, &i, &e]);
+        check(&mut tm, &w, &vars);
+    }
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::assert_equivalent;
+use fol::btor2::{dump_btor2, parse_btor2};
+use fol::op::*;
+use fol::*;
+
+/// The inits, nexts, bads and constraints of `ts` in a fixed order.
+fn roots(ts: &TransitionSys) -> Vec<Term> {
+    let mut roots: Vec<Term> = ts
+        .latch
+        .iter()
+        .filter_map(|l| ts.init.get(l))
+        .cloned()
+        .collect();
+    roots.extend(ts.latch.iter().map(|l| ts.next[l].clone()));
+    roots.extend(ts.bad.iter().cloned());
+    roots.extend(ts.constraint.iter().cloned());
+    roots
+}
+
+#[test]
+fn dump_parse_roundtrip() {
+    let mut tm = TermManager::new();
+    let mut ts = TransitionSys::new();
+    let i = tm.new_named_var("i", Sort::Bv(3));
+    let a = tm.new_named_var("a", Sort::Array(1, 2));
+    let j = tm.new_var(Sort::Bv(1));
+    ts.input.extend([i.clone(), a.clone(), j.clone()]);
+    let l = tm.new_named_var("l", Sort::Bv(3));
+    let m = tm.new_named_var("m", Sort::Bv(2));
+    let s = &l + &i;
+    let r = tm.new_op_term(Read, [&a, &j]);
+    let next_m = tm.new_op_term(Rol, [&r, &s.slice(1, 0)]);
+    let zero = tm.bv_const_zero(3);
+    ts.add_latch(l.clone(), Some(zero), tm.new_op_term(Ite, [&j, &s, &l]));
+    ts.add_latch(m.clone(), None, next_m);
+    let words = [
+        tm.new_op_term(Sra, [&s, &i]),
+        tm.new_op_term(Udiv, [&s, &l]),
+        tm.new_op_term(Srem, [&l, &s]),
+        tm.new_op_term(Concat, [&m, &j]),
+        m.sext(1),
+        m.uext(1),
+    ];
+    for w in words.iter() {
+        let b = tm.new_op_term(Slt, [w, &s]);
+        ts.bad.push(&b ^ &tm.new_op_term(Redxor, [w]));
+    }
+    ts.bad.push(tm.new_op_term(Umulo, [&s, &i]));
+    ts.constraint.push(tm.new_op_term(Ult, [&i, &l]));
+
+    let src = dump_btor2(&ts);
+    let mut tm2 = TermManager::new();
+    let parsed = parse_btor2(&mut tm2, &src).unwrap();
+    assert_eq!(dump_btor2(&parsed), src);
+    assert_eq!(parsed.input.len(), ts.input.len());
+    assert_eq!(parsed.latch.len(), ts.latch.len());
+    assert_eq!(parsed.bad.len(), ts.bad.len());
+    assert_eq!(parsed.constraint.len(), ts.constraint.len());
+    let vars: Vec<Term> = ts.input.iter().chain(ts.latch.iter()).cloned().collect();
+    let parsed_vars: Vec<Term> = parsed
+        .input
+        .iter()
+        .chain(parsed.latch.iter())
+        .cloned()
+        .collect();
+    assert_equivalent(&roots(&ts), &vars, &roots(&parsed), &parsed_vars);
+}
+
+#[test]
+fn parse_errors() {
+    let mut tm = TermManager::new();
+    for src in [
+        "1 sort bitvec",
+        "1 sort bitvec 4\n2 input 3",
+        "1 sort bitvec 4\n2 input 1\n3 add 1 2 5",
+        "1 sort bitvec 4\n2 sort bitvec 1\n3 input 1\n4 input 2\n5 add 1 3 4",
+        "1 sort bitvec 4\n2 input 1\n3 slice 1 2 4 0",
+        "1 sort bitvec 4\n2 const 1 012",
+        "1 sort bitvec 1\n2 input 1\n3 justice 1 2",
+        "1 sort bitvec 1\n2 input 1\n3 fair 2",
+    ] {
+        assert!(parse_btor2(&mut tm, src).is_err(), "{src}");
+    }
+}
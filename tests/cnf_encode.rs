@@ -0,0 +1,161 @@
+use fol::op::*;
+use fol::*;
+use giputils::hash::GHashMap;
+use logic_form::{DagCnf, Lit};
+
+#[inline]
+fn value(l: Lit, vals: &[bool]) -> bool {
+    vals[l.var().index()] == l.polarity()
+}
+
+/// Checks `bv_cnf_encode` of `t` against `Model::eval` under every
+/// assignment of `vars`. Every var of the `DagCnf` defined after the inputs
+/// must take exactly one value satisfying its relation, which gives the
+/// value of `t`.
+fn check(t: &Term, vars: &[Term]) {
+    let mut dc = DagCnf::new();
+    let mut map = GHashMap::new();
+    let inputs: Vec<_> = vars
+        .iter()
+        .map(|v| v.bv_cnf_encode(&mut dc, &mut map))
+        .collect();
+    let out = t.bv_cnf_encode(&mut dc, &mut map);
+    let n = dc.max_var().index() + 1;
+    let mut is_input = vec![false; n];
+    for l in inputs.iter().flat_map(|lits| lits.iter()) {
+        is_input[l.var().index()] = true;
+    }
+    let num_bits: usize = vars.iter().map(|v| v.sort().size()).sum();
+    for a in 0..1usize << num_bits {
+        let mut vals = vec![false; n];
+        vals[0] = true;
+        let mut model = Model::new();
+        let mut off = 0;
+        for (v, lits) in vars.iter().zip(inputs.iter()) {
+            let bits: Vec<bool> = (0..v.sort().size())
+                .map(|i| a >> (off + i) & 1 == 1)
+                .collect();
+            for (l, b) in lits.iter().zip(bits.iter()) {
+                vals[l.var().index()] = *b == l.polarity();
+            }
+            off += bits.len();
+            model.insert(v.clone(), BvConst::new(&bits));
+        }
+        for i in (1..n).filter(|i| !is_input[*i]) {
+            let sat: Vec<bool> = [false, true]
+                .into_iter()
+                .filter(|b| {
+                    vals[i] = *b;
+                    dc.cnf[i].iter().all(|c| c.iter().any(|l| value(*l, &vals)))
+                })
+                .collect();
+            assert_eq!(sat.len(), 1, "var {i} of {t:?} under {a}");
+            vals[i] = sat[0];
+        }
+        let got: Vec<bool> = out.iter().map(|l| value(*l, &vals)).collect();
+        let exp = model.eval(t, &mut GHashMap::new());
+        assert_eq!(BvConst::new(&got), exp, "{t:?} under {a}");
+    }
+}
+
+#[test]
+fn binary_ops() {
+    let mut tm = TermManager::new();
+    let ops: Vec<DynOp> = vec![
+        And.into(),
+        Or.into(),
+        Xor.into(),
+        Eq.into(),
+        Ult.into(),
+        Slt.into(),
+        Sll.into(),
+        Srl.into(),
+        Sra.into(),
+        Rol.into(),
+        Ror.into(),
+        Concat.into(),
+        Add.into(),
+        Mul.into(),
+        Udiv.into(),
+        Urem.into(),
+        Sdiv.into(),
+        Srem.into(),
+        Smod.into(),
+        Uaddo.into(),
+        Saddo.into(),
+        Usubo.into(),
+        Ssubo.into(),
+        Umulo.into(),
+        Smulo.into(),
+        Sdivo.into(),
+    ];
+    for w in 1..=4 {
+        let x = tm.new_var(Sort::Bv(w));
+        let y = tm.new_var(Sort::Bv(w));
+        let c = tm.bv_const_from_usize(0b101 & ((1 << w) - 1), w);
+        for op in ops.iter() {
+            for (a, b) in [(&x, &y), (&x, &x), (&x, &c), (&c, &y)] {
+                let t = tm.new_op_term(op.clone(), [a, b]);
+                check(&t, &[x.clone(), y.clone()]);
+            }
+        }
+    }
+}
+
+#[test]
+fn mixed_width_ops() {
+    let mut tm = TermManager::new();
+    for (w, v) in [(1, 2), (2, 3), (3, 1)] {
+        let x = tm.new_var(Sort::Bv(w));
+        let y = tm.new_var(Sort::Bv(v));
+        for op in [DynOp::from(Concat), Rol.into(), Ror.into()] {
+            let t = tm.new_op_term(op, [&x, &y]);
+            check(&t, &[x.clone(), y.clone()]);
+        }
+    }
+}
+
+#[test]
+fn unary_ops() {
+    let mut tm = TermManager::new();
+    for w in 1..=4 {
+        let x = tm.new_var(Sort::Bv(w));
+        let vars = [x.clone()];
+        check(&!&x, &vars);
+        check(&tm.new_op_term(Redxor, [&x]), &vars);
+        check(&x.sext(2), &vars);
+        check(&x.uext(2), &vars);
+        for hi in 0..w {
+            for lo in 0..=hi {
+                check(&x.slice(hi, lo), &vars);
+            }
+        }
+    }
+}
+
+#[test]
+fn ite() {
+    let mut tm = TermManager::new();
+    let c = tm.new_var(Sort::Bv(1));
+    for w in 1..=3 {
+        let x = tm.new_var(Sort::Bv(w));
+        let y = tm.new_var(Sort::Bv(w));
+        let t = tm.new_op_term(Ite, [&c, &x, &y]);
+        check(&t, &[c.clone(), x.clone(), y.clone()]);
+    }
+}
+
+#[test]
+fn arrays() {
+    let mut tm = TermManager::new();
+    let a = tm.new_var(Sort::Array(2, 2));
+    let i = tm.new_var(Sort::Bv(2));
+    let e = tm.new_var(Sort::Bv(2));
+    let vars = [a.clone(), i.clone(), e.clone()];
+    check(&tm.new_op_term(Read, [&a, &i]), &vars);
+    let w = tm.new_op_term(Write, [&a, &i, &e]);
+    check(&w, &vars);
+    let j = tm.bv_const_from_usize(1, 2);
+    check(&tm.new_op_term(Read, [&w, &j]), &vars);
+    check(&tm.new_op_term(Eq, [&w, &a]), &vars);
+}
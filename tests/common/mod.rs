@@ -0,0 +1,32 @@
+use fol::*;
+use giputils::hash::GHashMap;
+
+/// Asserts that every term of `x` over the vars `x_vars` evaluates as the
+/// term of `y` at the same position over the vars `y_vars` at the same
+/// position, under every assignment of the vars.
+pub fn assert_equivalent(x: &[Term], x_vars: &[Term], y: &[Term], y_vars: &[Term]) {
+    assert_eq!(x.len(), y.len());
+    assert_eq!(x_vars.len(), y_vars.len());
+    let num_bits: usize = x_vars.iter().map(|v| v.sort().size()).sum();
+    for a in 0..1usize << num_bits {
+        let (mut mx, mut my) = (Model::new(), Model::new());
+        let mut off = 0;
+        for (vx, vy) in x_vars.iter().zip(y_vars) {
+            assert_eq!(vx.sort(), vy.sort());
+            let bits: Vec<bool> = (0..vx.sort().size())
+                .map(|i| a >> (off + i) & 1 == 1)
+                .collect();
+            off += bits.len();
+            mx.insert(vx.clone(), BvConst::new(&bits));
+            my.insert(vy.clone(), BvConst::new(&bits));
+        }
+        let (mut cx, mut cy) = (GHashMap::new(), GHashMap::new());
+        for (tx, ty) in x.iter().zip(y) {
+            assert_eq!(
+                mx.eval(tx, &mut cx),
+                my.eval(ty, &mut cy),
+                "{tx:?} under {a}"
+            );
+        }
+    }
+}
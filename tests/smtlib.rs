@@ -0,0 +1,90 @@
+mod common;
+
+use common::assert_equivalent;
+use fol::op::*;
+use fol::smtlib::{Smtlib, dump_smtlib};
+use fol::*;
+
+#[test]
+fn print_parse_roundtrip() {
+    let mut tm = TermManager::new();
+    let x = tm.new_named_var("x", Sort::Bv(3));
+    let y = tm.new_named_var("y", Sort::Bv(3));
+    let z = tm.new_named_var("z", Sort::Bv(2));
+    let c = tm.new_named_var("c", Sort::Bv(1));
+    let s = &x + &y;
+    let p = tm.new_op_term(Mul, [&x, &y]);
+    let k = tm.bv_const_from_usize(2, 3);
+    let mut roots = Vec::new();
+    for op in [
+        DynOp::from(Eq),
+        Ult.into(),
+        Slt.into(),
+        Uaddo.into(),
+        Smulo.into(),
+    ] {
+        roots.push(tm.new_op_term(op, [&s, &x]));
+    }
+    let words = [
+        tm.new_op_term(Ite, [&c, &s, &p]),
+        tm.new_op_term(Sll, [&s, &y]),
+        tm.new_op_term(Sra, [&x, &s]),
+        tm.new_op_term(Udiv, [&s, &y]),
+        tm.new_op_term(Smod, [&x, &s]),
+        tm.new_op_term(Rol, [&s, &z]),
+        tm.new_op_term(Ror, [&x, &k]),
+        tm.new_op_term(Concat, [&z, &c]),
+        z.sext(1),
+        s.slice(2, 0),
+    ];
+    for w in words.iter() {
+        let v = tm.new_op_term(Redxor, [w]);
+        roots.push(&v ^ &tm.new_op_term(Eq, [w, &w.mk_bv_const_zero()]));
+    }
+    let src = dump_smtlib(&roots);
+    let mut tm2 = TermManager::new();
+    let parsed = Smtlib::parse(&mut tm2, &src).unwrap();
+    let vars = [x, y, z, c];
+    let parsed_vars: Vec<Term> = ["x", "y", "z", "c"]
+        .iter()
+        .map(|n| parsed.vars[*n].clone())
+        .collect();
+    assert_equivalent(&roots, &vars, &parsed.assertions, &parsed_vars);
+}
+
+#[test]
+fn parse_errors() {
+    let mut tm = TermManager::new();
+    for src in [
+        "(declare-const |x (_ BitVec 4))",
+        "(echo \"abc)",
+        "(assert true))",
+        "(assert (bvadd #b01 #b001))",
+        "(declare-fun f ((_ BitVec 2)) Bool)",
+        "(define-fun f ((x Bool)) Bool (f x)) (assert (f true))",
+        "(assert (= ((_ extract 4 0) #b01) #b01))",
+        "(declare-const x Bool) (declare-const x Bool)",
+        "(pop 1)",
+    ] {
+        assert!(Smtlib::parse(&mut tm, src).is_err(), "{src}");
+    }
+}
+
+#[test]
+fn solver_commands() {
+    let mut tm = TermManager::new();
+    let src = "(set-option :produce-models true)
+        (declare-const x (_ BitVec 4))
+        (push 1)
+        (declare-const y Bool)
+        (assert y)
+        (pop 1)
+        (assert (= x #x3))
+        (check-sat)
+        (get-model)
+        (get-value (x))
+        (exit)";
+    let parsed = Smtlib::parse(&mut tm, src).unwrap();
+    assert_eq!(parsed.assertions.len(), 1);
+    assert_eq!(parsed.vars.len(), 1);
+}